@@ -0,0 +1,229 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use remotia_buffer_utils::{BufMut, BytesMut};
+use remotia_core::traits::{BorrowMutFrameProperties, FrameProcessor};
+use remotia_image_utils::{
+    file::{read_image, ImageFileFormat},
+    format::PixelFormat,
+};
+use tokio::time::{Interval, MissedTickBehavior};
+
+/// Reads a sequence of PNG, PPM or raw image files, in lexicographical order of their paths.
+///
+/// Decoded images are converted to the configured pixel format, while raw files are copied
+/// as they are and are expected to already match it. All the images must have the same
+/// resolution, which for raw files must be set explicitly: reading stops at the first image that
+/// does not match it.
+pub struct ImageSequenceCapturer<K> {
+    buffer_key: K,
+    paths: Vec<PathBuf>,
+
+    format: PixelFormat,
+    resolution: Option<(usize, usize)>,
+
+    looping: bool,
+    frame_duration: Option<Duration>,
+    interval: Option<Interval>,
+
+    current_image: usize,
+    converted_frame: Vec<u8>,
+}
+
+impl<K> ImageSequenceCapturer<K> {
+    /// Collects the images matching a glob pattern (e.g. `frames/*.png`).
+    /// The resolution of the sequence is read from its first image, unless it is a raw file.
+    pub fn new(buffer_key: K, pattern: &str) -> io::Result<Self> {
+        let mut paths: Vec<PathBuf> = glob::glob(pattern)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?
+            .filter_map(Result::ok)
+            .filter(|path| ImageFileFormat::from_path(path).is_some())
+            .collect();
+        paths.sort();
+
+        let first_path = paths.first().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No images matching '{}'", pattern),
+            )
+        })?;
+
+        let resolution = match ImageFileFormat::from_path(first_path) {
+            Some(ImageFileFormat::Raw) => None,
+            _ => {
+                let image = read_image(first_path)?;
+                Some((image.width, image.height))
+            }
+        };
+
+        debug!(
+            "Found {} images matching '{}' ({:?})",
+            paths.len(),
+            pattern,
+            resolution
+        );
+
+        Ok(Self {
+            buffer_key,
+            paths,
+            format: PixelFormat::Rgba,
+            resolution,
+            looping: false,
+            frame_duration: None,
+            interval: None,
+            current_image: 0,
+            converted_frame: Vec::new(),
+        })
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the resolution of the images, required for raw files which carry no header
+    pub fn resolution(mut self, width: usize, height: usize) -> Self {
+        self.resolution = Some((width, height));
+        self
+    }
+
+    /// Paces reads to the given frame rate
+    pub fn frame_rate(mut self, frames_per_second: f64) -> Self {
+        self.frame_duration = Some(Duration::from_secs_f64(1.0 / frames_per_second));
+        self
+    }
+
+    /// Restarts from the first image instead of stopping after the last one
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn images_count(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Resolution of the images, unknown (zero) for raw files until it is set
+    pub fn width(&self) -> usize {
+        self.resolution.map_or(0, |(width, _)| width)
+    }
+
+    pub fn height(&self) -> usize {
+        self.resolution.map_or(0, |(_, height)| height)
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.format.buffer_size(self.width(), self.height())
+    }
+
+    async fn wait_next_frame(&mut self) {
+        let frame_duration = match self.frame_duration {
+            Some(frame_duration) => frame_duration,
+            None => return,
+        };
+
+        let interval = self.interval.get_or_insert_with(|| {
+            let mut interval = tokio::time::interval(frame_duration);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+
+        interval.tick().await;
+    }
+
+    fn next_path(&mut self) -> Option<PathBuf> {
+        if self.current_image >= self.paths.len() {
+            if !self.looping {
+                return None;
+            }
+
+            debug!("Restarting image sequence");
+            self.current_image = 0;
+        }
+
+        let path = self.paths[self.current_image].clone();
+        self.current_image += 1;
+        Some(path)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        self.converted_frame.clear();
+
+        let (width, height) = self.resolution.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The resolution of raw images must be set",
+            )
+        })?;
+
+        if let Some(ImageFileFormat::Raw) = ImageFileFormat::from_path(path) {
+            self.converted_frame = std::fs::read(path)?;
+
+            let expected_size = self.format.buffer_size(width, height);
+            if self.converted_frame.len() != expected_size {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Raw image of {} bytes, expected {} bytes for a {}x{} {:?} frame",
+                        self.converted_frame.len(),
+                        expected_size,
+                        width,
+                        height,
+                        self.format
+                    ),
+                ));
+            }
+        } else {
+            let image = read_image(path)?;
+            if (image.width, image.height) != (width, height) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Image of {}x{}, expected {}x{}",
+                        image.width, image.height, width, height
+                    ),
+                ));
+            }
+
+            image.write_as(self.format, &mut self.converted_frame);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for ImageSequenceCapturer<K>
+where
+    K: Send,
+    F: BorrowMutFrameProperties<K, BytesMut> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        self.wait_next_frame().await;
+
+        let path = match self.next_path() {
+            Some(path) => path,
+            None => {
+                debug!("No more images to read");
+                return None;
+            }
+        };
+
+        debug!("Reading image {:?}...", path);
+
+        if let Err(error) = self.load(&path) {
+            warn!("Unable to read image {:?}: {}", path, error);
+            return None;
+        }
+
+        let output_buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        output_buffer.put(&self.converted_frame[..]);
+
+        Some(frame_data)
+    }
+}
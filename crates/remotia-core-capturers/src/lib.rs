@@ -1,2 +1,7 @@
+pub mod image_sequence;
+pub mod pattern;
 pub mod scrap;
 pub mod y4m;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,304 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::{BufMut, BytesMut};
+use remotia_core::traits::{BorrowMutFrameProperties, FrameProcessor};
+use remotia_image_utils::{convert::convert_frame, format::PixelFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestPattern {
+    /// Static vertical color bars
+    ColorBars,
+
+    /// Diagonal gradient moving horizontally over time
+    MovingGradient,
+
+    /// Color noise, different at each frame
+    Noise,
+
+    /// Lines of dark blocks on a light background scrolling upwards, resembling text
+    ScrollingBlocks,
+}
+
+const COLOR_BARS: [[u8; 3]; 8] = [
+    [255, 255, 255],
+    [255, 255, 0],
+    [0, 255, 255],
+    [0, 255, 0],
+    [255, 0, 255],
+    [255, 0, 0],
+    [0, 0, 255],
+    [0, 0, 0],
+];
+
+/// 3x5 bitmaps of the decimal digits, one row per byte using the three least significant bits
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+const LINE_HEIGHT: usize = 16;
+const BLOCK_HEIGHT: usize = 10;
+const SCROLL_SPEED: usize = 2;
+
+/// Deterministic synthetic frame source, producing the same sequence of frames for the same
+/// configuration. Useful to run pipelines on machines without displays or input files.
+pub struct TestPatternCapturer<K> {
+    buffer_key: K,
+    pattern: TestPattern,
+
+    width: usize,
+    height: usize,
+    format: PixelFormat,
+
+    seed: u64,
+    frame_counter: bool,
+
+    frame_index: u64,
+    rgba_frame: Vec<u8>,
+    converted_frame: Vec<u8>,
+}
+
+impl<K> TestPatternCapturer<K> {
+    pub fn new(buffer_key: K, pattern: TestPattern, width: u32, height: u32) -> Self {
+        Self {
+            buffer_key,
+            pattern,
+            width: width as usize,
+            height: height as usize,
+            format: PixelFormat::Rgba,
+            seed: 0,
+            frame_counter: false,
+            frame_index: 0,
+            rgba_frame: Vec::new(),
+            converted_frame: Vec::new(),
+        }
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the seed of the random patterns
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Burns the index of the frame in the top left corner
+    pub fn frame_counter(mut self) -> Self {
+        self.frame_counter = true;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.format.buffer_size(self.width, self.height)
+    }
+
+    fn render(&mut self) {
+        self.rgba_frame
+            .resize(PixelFormat::Rgba.buffer_size(self.width, self.height), 0);
+
+        match self.pattern {
+            TestPattern::ColorBars => self.render_color_bars(),
+            TestPattern::MovingGradient => self.render_moving_gradient(),
+            TestPattern::Noise => self.render_noise(),
+            TestPattern::ScrollingBlocks => self.render_scrolling_blocks(),
+        }
+
+        if self.frame_counter {
+            self.render_frame_counter();
+        }
+    }
+
+    fn pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut [u8])> {
+        let width = self.width.max(1);
+        self.rgba_frame
+            .chunks_exact_mut(4)
+            .enumerate()
+            .map(move |(index, pixel)| (index % width, index / width, pixel))
+    }
+
+    fn render_color_bars(&mut self) {
+        let bar_width = self.width.div_ceil(COLOR_BARS.len()).max(1);
+        for (x, _, pixel) in self.pixels_mut() {
+            let [r, g, b] = COLOR_BARS[(x / bar_width).min(COLOR_BARS.len() - 1)];
+            pixel.copy_from_slice(&[r, g, b, 255]);
+        }
+    }
+
+    fn render_moving_gradient(&mut self) {
+        let (width, height) = (self.width.max(1), self.height.max(1));
+        let shift = self.frame_index as usize * 4;
+        let blue = (self.frame_index * 2 % 256) as u8;
+
+        for (x, y, pixel) in self.pixels_mut() {
+            let red = ((x + shift) % width * 255 / width) as u8;
+            let green = (y * 255 / height) as u8;
+            pixel.copy_from_slice(&[red, green, blue, 255]);
+        }
+    }
+
+    fn render_noise(&mut self) {
+        let mut state = mix(self.seed ^ mix(self.frame_index));
+        for pixel in self.rgba_frame.chunks_exact_mut(4) {
+            let value = xorshift(&mut state).to_le_bytes();
+            pixel.copy_from_slice(&[value[0], value[1], value[2], 255]);
+        }
+    }
+
+    fn render_scrolling_blocks(&mut self) {
+        let seed = self.seed;
+        let width = self.width;
+        let offset = self.frame_index as usize * SCROLL_SPEED;
+
+        for (x, y, pixel) in self.pixels_mut() {
+            let scrolled_y = y + offset;
+            let line = (scrolled_y / LINE_HEIGHT) as u64;
+            let in_block_row = scrolled_y % LINE_HEIGHT < BLOCK_HEIGHT;
+
+            let color = if in_block_row && is_block(seed, line, x, width) {
+                [40, 40, 40, 255]
+            } else {
+                [235, 235, 235, 255]
+            };
+
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    fn render_frame_counter(&mut self) {
+        let digits = self.frame_index.to_string();
+        let scale = (self.height / 60).max(2);
+        let margin = scale;
+
+        let digit_width = 3 * scale;
+        let box_width = (digits.len() * (digit_width + scale) + scale + 2 * margin).min(self.width);
+        let box_height = (5 * scale + 2 * margin).min(self.height);
+
+        let width = self.width;
+        let mut set_pixel = |x: usize, y: usize, value: u8| {
+            if x < box_width && y < box_height {
+                let offset = (y * width + x) * 4;
+                self.rgba_frame[offset..offset + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        };
+
+        for y in 0..box_height {
+            for x in 0..box_width {
+                set_pixel(x, y, 0);
+            }
+        }
+
+        for (position, digit) in digits.bytes().enumerate() {
+            let bitmap = DIGITS[(digit - b'0') as usize];
+            let origin_x = margin + scale + position * (digit_width + scale);
+
+            for (row, bits) in bitmap.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            set_pixel(
+                                origin_x + column * scale + dx,
+                                margin + row * scale + dy,
+                                255,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Deterministically splits a line into words of variable length separated by spaces
+fn is_block(seed: u64, line: u64, x: usize, width: usize) -> bool {
+    let mut state = mix(seed ^ mix(line + 1));
+    let line_length = width / 2 + (xorshift(&mut state) as usize) % (width / 2 + 1);
+
+    if x >= line_length {
+        return false;
+    }
+
+    let mut word_start = 8;
+    while word_start <= x {
+        let word_length = 8 + (xorshift(&mut state) % 48) as usize;
+        if x < word_start + word_length {
+            return true;
+        }
+        word_start += word_length + 8;
+    }
+
+    false
+}
+
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+fn xorshift(state: &mut u64) -> u64 {
+    let mut value = *state;
+    value ^= value << 13;
+    value ^= value >> 7;
+    value ^= value << 17;
+    *state = value;
+    value
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for TestPatternCapturer<K>
+where
+    K: Send,
+    F: BorrowMutFrameProperties<K, BytesMut> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        debug!(
+            "Generating {:?} frame {}...",
+            self.pattern, self.frame_index
+        );
+
+        self.render();
+
+        let output_buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        if self.format == PixelFormat::Rgba {
+            output_buffer.put(&self.rgba_frame[..]);
+        } else {
+            self.converted_frame.resize(self.buffer_size(), 0);
+            convert_frame(
+                &self.rgba_frame,
+                PixelFormat::Rgba,
+                &mut self.converted_frame,
+                self.format,
+                (self.width, self.height),
+            );
+            output_buffer.put(&self.converted_frame[..]);
+        }
+
+        self.frame_index += 1;
+
+        Some(frame_data)
+    }
+}
@@ -1,67 +1,264 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
 use async_trait::async_trait;
-use log::debug;
-use remotia_core::traits::{FrameProcessor, BorrowMutFrameProperties};
-use scrap::{Capturer, Display};
+use log::{debug, warn};
 use remotia_buffer_utils::{BufMut, BytesMut};
+use remotia_core::{
+    common::helpers::time::now_timestamp,
+    error::DropReason,
+    traits::{BorrowMutFrameProperties, FrameError, FrameProcessor, OptionalPropertyKey},
+};
+use remotia_image_utils::geometry::Rect;
+use scrap::{Capturer, Display};
 
-use core::slice;
+const BYTES_PER_PIXEL: usize = 4;
 
-pub struct ScrapFrameCapturer<K> {
+/// Captures BGRA frames of a display.
+///
+/// Captured rows are stripped of their padding, so that the buffer always contains
+/// `width * height * 4` tightly packed bytes. When no new frame is ready, capturing is retried.
+///
+/// By default, capture errors are fatal. Once `report_errors` is set, they are reported as
+/// `DropReason::CaptureError` instead, and a timeout may be set on the wait for a new frame,
+/// reporting `DropReason::NoCapturedFrames` when it expires.
+pub struct ScrapFrameCapturer<K, P = (), E = ()> {
     buffer_key: K,
+    timestamp_key: P,
+    errors: E,
+
     capturer: Capturer,
+    display_width: usize,
+    display_height: usize,
+    region: Rect,
+
+    timeout: Option<Duration>,
+    retry_interval: Duration,
 }
 
 // TODO: Evaluate a safer way to move the capturer to another thread
 // Necessary for multi-threaded pipelines
-unsafe impl<K> Send for ScrapFrameCapturer<K> {}
+unsafe impl<K, P, E> Send for ScrapFrameCapturer<K, P, E> {}
+
+/// Handling of the capture errors of a `ScrapFrameCapturer`: `()` panics, while
+/// `ReportErrors` reports them through `FrameError`
+pub trait CaptureErrorHandler<F>: Send {
+    fn handle(&self, frame_data: &mut F, reason: DropReason);
+}
+
+impl<F> CaptureErrorHandler<F> for () {
+    fn handle(&self, _frame_data: &mut F, reason: DropReason) {
+        panic!("Scrap capture error: {:?}", reason);
+    }
+}
+
+/// Reports the capture errors as the `DropReason` of the frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReportErrors;
+
+impl<F: FrameError<DropReason>> CaptureErrorHandler<F> for ReportErrors {
+    fn handle(&self, frame_data: &mut F, reason: DropReason) {
+        frame_data.report_error(reason);
+    }
+}
+
+pub(crate) enum CaptureOutcome {
+    Captured,
+    WouldBlock,
+    Failed(io::Error),
+}
 
 impl<K> ScrapFrameCapturer<K> {
     pub fn new(buffer_key: K, capturer: Capturer) -> Self {
+        let display_width = capturer.width();
+        let display_height = capturer.height();
+
         Self {
             buffer_key,
+            timestamp_key: (),
+            errors: (),
             capturer,
+            display_width,
+            display_height,
+            region: Rect::new(0, 0, display_width, display_height),
+            timeout: None,
+            retry_interval: Duration::from_millis(1),
         }
     }
 
     pub fn new_from_primary(buffer_key: K) -> Self {
         let display = Display::primary().expect("Couldn't find primary display.");
         let capturer = Capturer::new(display).expect("Couldn't begin capture.");
-        Self {
-            buffer_key,
-            capturer,
+        Self::new(buffer_key, capturer)
+    }
+
+    /// Captures the display at the given index of `Display::all()`
+    pub fn new_from_display_index(buffer_key: K, index: usize) -> io::Result<Self> {
+        let display = Display::all()?.into_iter().nth(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No display with index {}", index),
+            )
+        })?;
+
+        Ok(Self::new(buffer_key, Capturer::new(display)?))
+    }
+}
+
+impl<K, E> ScrapFrameCapturer<K, (), E> {
+    /// Sets the key of the property the capture timestamp is written to
+    pub fn timestamp_key<P>(self, key: P) -> ScrapFrameCapturer<K, Option<P>, E> {
+        self.with(|_, errors| (Some(key), errors))
+    }
+}
+
+impl<K, P> ScrapFrameCapturer<K, P, ()> {
+    /// Reports the capture errors through `FrameError<DropReason>` instead of panicking
+    pub fn report_errors(self) -> ScrapFrameCapturer<K, P, ReportErrors> {
+        self.with(|timestamp_key, _| (timestamp_key, ReportErrors))
+    }
+}
+
+impl<K, P> ScrapFrameCapturer<K, P, ReportErrors> {
+    /// Stops waiting for a new frame after the given time, reporting the frame as dropped.
+    /// A zero timeout never waits. By default, capturing is retried indefinitely.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl<K, P, E> ScrapFrameCapturer<K, P, E> {
+    fn with<Q, R>(self, map: impl FnOnce(P, E) -> (Q, R)) -> ScrapFrameCapturer<K, Q, R> {
+        let (timestamp_key, errors) = map(self.timestamp_key, self.errors);
+        ScrapFrameCapturer {
+            buffer_key: self.buffer_key,
+            timestamp_key,
+            errors,
+            capturer: self.capturer,
+            display_width: self.display_width,
+            display_height: self.display_height,
+            region: self.region,
+            timeout: self.timeout,
+            retry_interval: self.retry_interval,
         }
     }
 
+    /// Captures only the given sub-rectangle of the display
+    pub fn region(mut self, region: Rect) -> Self {
+        assert!(
+            !region.is_empty() && region.fits(self.display_width, self.display_height),
+            "Capture region {:?} does not fit a {}x{} display",
+            region,
+            self.display_width,
+            self.display_height
+        );
+
+        self.region = region;
+        self
+    }
+
+    /// Sets how long to sleep before retrying when no new frame is ready
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
     pub fn width(&self) -> usize {
-        self.capturer.width()
+        self.region.width
     }
 
     pub fn height(&self) -> usize {
-        self.capturer.height()
+        self.region.height
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.region.area() * BYTES_PER_PIXEL
     }
 
-    pub fn buffer_size(&mut self) -> usize {
-        self.capturer.frame().unwrap().len()
+    fn capture_into(&mut self, output_buffer: &mut BytesMut) -> CaptureOutcome {
+        let frame = match self.capturer.frame() {
+            Ok(frame) => frame,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                return CaptureOutcome::WouldBlock
+            }
+            Err(error) => return CaptureOutcome::Failed(error),
+        };
+
+        let stride = frame.len() / self.display_height;
+        copy_region(&frame, stride, self.region, output_buffer);
+
+        CaptureOutcome::Captured
+    }
+}
+
+/// Copies the pixels of a region of a BGRA frame whose rows are `stride` bytes apart,
+/// leaving out the padding at the end of the rows
+pub(crate) fn copy_region(frame: &[u8], stride: usize, region: Rect, output: &mut impl BufMut) {
+    let row_size = region.width * BYTES_PER_PIXEL;
+    let row_offset = region.x * BYTES_PER_PIXEL;
+
+    for y in region.y..region.bottom() {
+        let start = y * stride + row_offset;
+        output.put(&frame[start..start + row_size]);
+    }
+}
+
+/// Repeats a capture until it yields a frame, sleeping `retry_interval` between the attempts.
+/// Fails when the capture does, or when no frame is captured within the timeout.
+pub(crate) async fn capture_with_retry(
+    mut capture: impl FnMut() -> CaptureOutcome,
+    timeout: Option<Duration>,
+    retry_interval: Duration,
+) -> Result<(), DropReason> {
+    let started_at = Instant::now();
+
+    loop {
+        match capture() {
+            CaptureOutcome::Captured => return Ok(()),
+            CaptureOutcome::WouldBlock => {
+                if let Some(timeout) = timeout {
+                    if started_at.elapsed() >= timeout {
+                        debug!("No new frame captured within {:?}", timeout);
+                        return Err(DropReason::NoCapturedFrames);
+                    }
+                }
+
+                tokio::time::sleep(retry_interval).await;
+            }
+            CaptureOutcome::Failed(error) => {
+                warn!("Scrap capture error: {}", error);
+                return Err(DropReason::CaptureError);
+            }
+        }
     }
 }
 
 #[async_trait]
-impl<F, K> FrameProcessor<F> for ScrapFrameCapturer<K>
+impl<F, K, P, E> FrameProcessor<F> for ScrapFrameCapturer<K, P, E>
 where
+    K: Send,
+    P: OptionalPropertyKey<F, u128>,
+    E: CaptureErrorHandler<F>,
     F: BorrowMutFrameProperties<K, BytesMut> + Send + 'static,
 {
     async fn process(&mut self, mut frame_data: F) -> Option<F> {
         debug!("Capturing...");
+
+        let (timeout, retry_interval) = (self.timeout, self.retry_interval);
         let output_buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
-        match self.capturer.frame() {
-            Ok(buffer) => {
-                let frame_slice = unsafe { slice::from_raw_parts(buffer.as_ptr(), buffer.len()) };
-                output_buffer.put(frame_slice);
-            }
-            Err(error) => {
-                panic!("Scrap capture error: {}", error);
-            }
+        let result =
+            capture_with_retry(|| self.capture_into(output_buffer), timeout, retry_interval).await;
+
+        match result {
+            Ok(()) => self
+                .timestamp_key
+                .set_property(&mut frame_data, now_timestamp()),
+            Err(reason) => self.errors.handle(&mut frame_data, reason),
         }
+
         Some(frame_data)
     }
 }
@@ -0,0 +1,615 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowMutFrameProperties, FrameProcessor, FrameProperties},
+};
+use y4m::{Colorspace, Ratio};
+
+use remotia_image_utils::{
+    convert::convert_frame,
+    file::{write_image, ImageFileFormat},
+    format::PixelFormat,
+    geometry::Rect,
+};
+
+use crate::{
+    image_sequence::ImageSequenceCapturer,
+    pattern::{TestPattern, TestPatternCapturer},
+    scrap::{capture_with_retry, copy_region, CaptureOutcome, ScrapFrameCapturer},
+    y4m::Y4MFrameCapturer,
+};
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+enum BufferType {
+    Frame,
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+enum Stat {
+    FrameIndex,
+    Timestamp,
+}
+
+#[derive(Default)]
+struct TestFrameData {
+    buffers: HashMap<BufferType, BytesMut>,
+    stats: HashMap<Stat, u128>,
+}
+
+impl TestFrameData {
+    fn new() -> Self {
+        let mut frame_data = Self::default();
+        frame_data
+            .buffers
+            .insert(BufferType::Frame, BytesMut::new());
+        frame_data
+    }
+
+    fn buffer(&self) -> &[u8] {
+        self.buffers.get(&BufferType::Frame).unwrap()
+    }
+}
+
+impl BorrowMutFrameProperties<BufferType, BytesMut> for TestFrameData {
+    fn get_mut_ref(&mut self, key: &BufferType) -> Option<&mut BytesMut> {
+        self.buffers.get_mut(key)
+    }
+}
+
+impl FrameProperties<Stat, u128> for TestFrameData {
+    fn set(&mut self, key: Stat, value: u128) {
+        self.stats.insert(key, value);
+    }
+
+    fn get(&self, key: &Stat) -> Option<u128> {
+        self.stats.get(key).copied()
+    }
+}
+
+/// Frame without any property besides its buffer
+struct BufferOnlyFrameData(BytesMut);
+
+impl BorrowMutFrameProperties<BufferType, BytesMut> for BufferOnlyFrameData {
+    fn get_mut_ref(&mut self, _key: &BufferType) -> Option<&mut BytesMut> {
+        Some(&mut self.0)
+    }
+}
+
+const WIDTH: usize = 4;
+const HEIGHT: usize = 2;
+
+/// Writes a 4x2 4:2:0 Y4M file whose n-th frame is filled with n
+fn write_y4m(name: &str, frames: u8, frame_rate: Ratio) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "remotia-capturers-{}-{}.y4m",
+        name,
+        std::process::id()
+    ));
+
+    let mut encoder = y4m::encode(WIDTH, HEIGHT, frame_rate)
+        .with_colorspace(Colorspace::C420)
+        .write_header(File::create(&path).unwrap())
+        .unwrap();
+
+    for value in 0..frames {
+        let y_plane = vec![value; WIDTH * HEIGHT];
+        let chroma_plane = vec![value; WIDTH * HEIGHT / 4];
+        let frame = y4m::Frame::new([&y_plane, &chroma_plane, &chroma_plane], None);
+        encoder.write_frame(&frame).unwrap();
+    }
+
+    path
+}
+
+async fn capture(capturer: &mut impl FrameProcessor<TestFrameData>) -> Option<(u8, u128, u128)> {
+    let frame_data = capturer.process(TestFrameData::new()).await?;
+    assert_eq!(frame_data.buffer().len(), WIDTH * HEIGHT * 3 / 2);
+
+    Some((
+        frame_data.buffer()[0],
+        frame_data.get(&Stat::FrameIndex).unwrap(),
+        frame_data.get(&Stat::Timestamp).unwrap(),
+    ))
+}
+
+#[tokio::test]
+async fn test_y4m_capturer_without_properties() {
+    let path = write_y4m("plain", 2, Ratio::new(25, 1));
+    let mut capturer = Y4MFrameCapturer::new(BufferType::Frame, &path).unwrap();
+
+    assert_eq!((capturer.width(), capturer.height()), (WIDTH, HEIGHT));
+    assert!(matches!(capturer.colorspace(), Colorspace::C420));
+    assert_eq!(capturer.buffer_size(), WIDTH * HEIGHT * 3 / 2);
+
+    for value in 0..2 {
+        let frame_data = capturer
+            .process(BufferOnlyFrameData(BytesMut::new()))
+            .await
+            .unwrap();
+        assert_eq!(frame_data.0.len(), capturer.buffer_size());
+        assert!(frame_data.0.iter().all(|sample| *sample == value));
+    }
+
+    assert!(capturer
+        .process(BufferOnlyFrameData(BytesMut::new()))
+        .await
+        .is_none());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_y4m_capturer_invalid_frame_rate() {
+    let path = write_y4m("zero-rate", 1, Ratio::new(0, 1));
+    assert!(matches!(
+        Y4MFrameCapturer::new(BufferType::Frame, &path),
+        Err(y4m::Error::BadInput)
+    ));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_y4m_capturer_looping() {
+    let path = write_y4m("looping", 3, Ratio::new(50, 1));
+    let mut capturer = Y4MFrameCapturer::new(BufferType::Frame, &path)
+        .unwrap()
+        .frame_index_key(Stat::FrameIndex)
+        .timestamp_key(Stat::Timestamp)
+        .looping();
+
+    for emitted in 0..7 {
+        let index = emitted % 3;
+        assert_eq!(
+            capture(&mut capturer).await,
+            Some((index as u8, index, emitted * 20))
+        );
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_y4m_capturer_seek() {
+    let path = write_y4m("seek", 4, Ratio::new(50, 1));
+    let mut capturer = Y4MFrameCapturer::new(BufferType::Frame, &path)
+        .unwrap()
+        .frame_index_key(Stat::FrameIndex)
+        .timestamp_key(Stat::Timestamp)
+        .looping()
+        .seek(2)
+        .unwrap();
+
+    assert_eq!(capture(&mut capturer).await, Some((2, 2, 40)));
+    assert_eq!(capture(&mut capturer).await, Some((3, 3, 60)));
+
+    // Looping restarts from the seeked frame, timestamps keep increasing
+    assert_eq!(capture(&mut capturer).await, Some((2, 2, 80)));
+
+    // Seeking backwards reopens the file
+    let mut capturer = Y4MFrameCapturer::new(BufferType::Frame, &path)
+        .unwrap()
+        .frame_index_key(Stat::FrameIndex)
+        .timestamp_key(Stat::Timestamp)
+        .seek(3)
+        .unwrap()
+        .seek(1)
+        .unwrap();
+
+    assert_eq!(capture(&mut capturer).await, Some((1, 1, 20)));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_y4m_capturer_realtime() {
+    let path = write_y4m("realtime", 5, Ratio::new(50, 1));
+    let mut capturer = Y4MFrameCapturer::new(BufferType::Frame, &path)
+        .unwrap()
+        .frame_index_key(Stat::FrameIndex)
+        .timestamp_key(Stat::Timestamp)
+        .realtime();
+
+    let started_at = Instant::now();
+    for _ in 0..5 {
+        capture(&mut capturer).await.unwrap();
+    }
+
+    // The first frame is read right away, each following one after a frame duration
+    let elapsed = started_at.elapsed();
+    assert!(elapsed >= Duration::from_millis(80), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(200), "{:?}", elapsed);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+async fn capture_patterns(
+    capturer: &mut TestPatternCapturer<BufferType>,
+    count: usize,
+) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    for _ in 0..count {
+        let frame_data = capturer.process(TestFrameData::new()).await.unwrap();
+        assert_eq!(frame_data.buffer().len(), capturer.buffer_size());
+        frames.push(frame_data.buffer().to_vec());
+    }
+    frames
+}
+
+fn rgba_pixel(frame: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+    let offset = (y * width + x) * 4;
+    frame[offset..offset + 4].try_into().unwrap()
+}
+
+/// Checks that two capturers with the same configuration produce the same frames
+async fn assert_deterministic(pattern: TestPattern, seed: u64) -> Vec<Vec<u8>> {
+    let mut first = TestPatternCapturer::new(BufferType::Frame, pattern, 16, 8).seed(seed);
+    let mut second = TestPatternCapturer::new(BufferType::Frame, pattern, 16, 8).seed(seed);
+
+    let frames = capture_patterns(&mut first, 4).await;
+    assert_eq!(frames, capture_patterns(&mut second, 4).await);
+    frames
+}
+
+#[tokio::test]
+async fn test_color_bars_pattern() {
+    let frames = assert_deterministic(TestPattern::ColorBars, 0).await;
+
+    // Bars are two pixels wide on a 16 pixels wide frame
+    let expected = [
+        [255, 255, 255, 255],
+        [255, 255, 0, 255],
+        [0, 255, 255, 255],
+        [0, 255, 0, 255],
+        [255, 0, 255, 255],
+        [255, 0, 0, 255],
+        [0, 0, 255, 255],
+        [0, 0, 0, 255],
+    ];
+    for x in 0..16 {
+        for y in 0..8 {
+            assert_eq!(rgba_pixel(&frames[0], 16, x, y), expected[x / 2]);
+        }
+    }
+
+    // Bars are static
+    assert!(frames.iter().all(|frame| *frame == frames[0]));
+}
+
+#[tokio::test]
+async fn test_moving_gradient_pattern() {
+    let frames = assert_deterministic(TestPattern::MovingGradient, 0).await;
+
+    assert_eq!(rgba_pixel(&frames[0], 16, 0, 0), [0, 0, 0, 255]);
+    assert_eq!(rgba_pixel(&frames[0], 16, 8, 4), [127, 127, 0, 255]);
+    assert_eq!(rgba_pixel(&frames[0], 16, 15, 7), [239, 223, 0, 255]);
+
+    // The gradient moves by 4 pixels per frame, while the blue channel grows by 2
+    assert_eq!(rgba_pixel(&frames[1], 16, 0, 0), [63, 0, 2, 255]);
+    assert_eq!(rgba_pixel(&frames[1], 16, 12, 4), [0, 127, 2, 255]);
+    assert_eq!(rgba_pixel(&frames[3], 16, 4, 0), [0, 0, 6, 255]);
+}
+
+#[tokio::test]
+async fn test_noise_pattern() {
+    let frames = assert_deterministic(TestPattern::Noise, 7).await;
+
+    assert_eq!(rgba_pixel(&frames[0], 16, 0, 0), [112, 77, 172, 255]);
+    assert_eq!(rgba_pixel(&frames[1], 16, 0, 0), [217, 29, 172, 255]);
+
+    assert_ne!(frames[0], frames[1]);
+    assert!(frames[0].chunks_exact(4).all(|pixel| pixel[3] == 255));
+
+    // Another seed gives another sequence
+    let mut capturer =
+        TestPatternCapturer::new(BufferType::Frame, TestPattern::Noise, 16, 8).seed(8);
+    assert_ne!(capture_patterns(&mut capturer, 1).await[0], frames[0]);
+}
+
+#[tokio::test]
+async fn test_scrolling_blocks_pattern() {
+    const BACKGROUND: [u8; 4] = [235, 235, 235, 255];
+    const BLOCK: [u8; 4] = [40, 40, 40, 255];
+
+    let mut capturer =
+        TestPatternCapturer::new(BufferType::Frame, TestPattern::ScrollingBlocks, 64, 32).seed(3);
+    let frames = capture_patterns(&mut capturer, 2).await;
+
+    for y in 0..32 {
+        for x in 0..64 {
+            let pixel = rgba_pixel(&frames[0], 64, x, y);
+            assert!(pixel == BACKGROUND || pixel == BLOCK);
+
+            // Lines start with a margin and are separated by background rows
+            if x < 8 || y % 16 >= 10 {
+                assert_eq!(pixel, BACKGROUND, "({}, {})", x, y);
+            }
+        }
+    }
+    assert!(frames[0].chunks_exact(4).any(|pixel| pixel == BLOCK));
+
+    // Each frame scrolls the content by two rows
+    assert_eq!(frames[1][..64 * 30 * 4], frames[0][64 * 2 * 4..]);
+
+    let second = assert_deterministic(TestPattern::ScrollingBlocks, 3).await;
+    assert_ne!(
+        second,
+        assert_deterministic(TestPattern::ScrollingBlocks, 4).await
+    );
+}
+
+#[tokio::test]
+async fn test_pattern_frame_counter() {
+    let mut capturer =
+        TestPatternCapturer::new(BufferType::Frame, TestPattern::ColorBars, 64, 60).frame_counter();
+    let frames = capture_patterns(&mut capturer, 2).await;
+
+    // Two pixels wide digits on a black box, after a two pixels margin
+    assert_eq!(rgba_pixel(&frames[0], 64, 0, 0), [0, 0, 0, 255]);
+    assert_eq!(rgba_pixel(&frames[0], 64, 4, 2), [255, 255, 255, 255]);
+    assert_eq!(rgba_pixel(&frames[0], 64, 6, 4), [0, 0, 0, 255]);
+
+    // The left pixel of the top row is lit for "0", not for "1"
+    assert_eq!(rgba_pixel(&frames[1], 64, 4, 2), [0, 0, 0, 255]);
+    assert_eq!(rgba_pixel(&frames[1], 64, 6, 2), [255, 255, 255, 255]);
+
+    // The rest of the frame is untouched
+    assert_eq!(rgba_pixel(&frames[0], 64, 63, 59), [0, 0, 0, 255]);
+    assert_eq!(rgba_pixel(&frames[0], 64, 0, 59), [255, 255, 255, 255]);
+}
+
+#[tokio::test]
+async fn test_pattern_conversion() {
+    let mut rgba_capturer =
+        TestPatternCapturer::new(BufferType::Frame, TestPattern::MovingGradient, 16, 8);
+    let mut yuv_capturer =
+        TestPatternCapturer::new(BufferType::Frame, TestPattern::MovingGradient, 16, 8)
+            .format(PixelFormat::Yuv420p);
+    assert_eq!(yuv_capturer.buffer_size(), 16 * 8 * 3 / 2);
+
+    for (rgba_frame, yuv_frame) in capture_patterns(&mut rgba_capturer, 2)
+        .await
+        .iter()
+        .zip(capture_patterns(&mut yuv_capturer, 2).await)
+    {
+        let mut expected = vec![0; yuv_frame.len()];
+        convert_frame(
+            rgba_frame,
+            PixelFormat::Rgba,
+            &mut expected,
+            PixelFormat::Yuv420p,
+            (16, 8),
+        );
+        assert_eq!(yuv_frame, expected);
+    }
+}
+
+/// Creates an empty folder for the images of a test
+fn images_folder(name: &str) -> PathBuf {
+    let folder =
+        std::env::temp_dir().join(format!("remotia-capturers-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+fn solid_rgba(value: u8, width: usize, height: usize) -> Vec<u8> {
+    [value, value / 2, 255 - value, 255].repeat(width * height)
+}
+
+async fn capture_images(
+    capturer: &mut ImageSequenceCapturer<BufferType>,
+    count: usize,
+) -> Vec<Option<Vec<u8>>> {
+    let mut frames = Vec::new();
+    for _ in 0..count {
+        let frame_data = capturer.process(TestFrameData::new()).await;
+        frames.push(frame_data.map(|frame_data| frame_data.buffer().to_vec()));
+    }
+    frames
+}
+
+#[tokio::test]
+async fn test_image_sequence_capturer() {
+    let folder = images_folder("sequence");
+    for (index, value) in [30, 10, 20].into_iter().enumerate() {
+        let path = folder.join(format!("frame{}.png", 2 - index));
+        let rgba = solid_rgba(value, 2, 2);
+        write_image(path, ImageFileFormat::Png, &rgba, PixelFormat::Rgba, (2, 2)).unwrap();
+    }
+    std::fs::write(folder.join("notes.txt"), "not an image").unwrap();
+
+    let pattern = folder.join("*").to_string_lossy().into_owned();
+    let mut capturer = ImageSequenceCapturer::new(BufferType::Frame, &pattern).unwrap();
+    assert_eq!(capturer.images_count(), 3);
+    assert_eq!((capturer.width(), capturer.height()), (2, 2));
+    assert_eq!(capturer.buffer_size(), 16);
+
+    // Images are read in lexicographical order of their paths
+    let expected: Vec<_> = [20, 10, 30]
+        .into_iter()
+        .map(|value| Some(solid_rgba(value, 2, 2)))
+        .chain([None])
+        .collect();
+    assert_eq!(capture_images(&mut capturer, 4).await, expected);
+
+    let mut capturer = ImageSequenceCapturer::new(BufferType::Frame, &pattern)
+        .unwrap()
+        .format(PixelFormat::Yuv420p)
+        .looping();
+    assert_eq!(capturer.buffer_size(), 6);
+
+    let frames = capture_images(&mut capturer, 4).await;
+    let mut first_frame = vec![0; 6];
+    convert_frame(
+        &solid_rgba(20, 2, 2),
+        PixelFormat::Rgba,
+        &mut first_frame,
+        PixelFormat::Yuv420p,
+        (2, 2),
+    );
+    assert_eq!(frames[0].as_ref(), Some(&first_frame));
+    assert_eq!(frames[3], frames[0]);
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[tokio::test]
+async fn test_image_sequence_capturer_rejects_other_resolutions() {
+    let folder = images_folder("resolutions");
+    for (name, width) in [("a", 2), ("b", 3)] {
+        let path = folder.join(format!("{}.ppm", name));
+        let rgba = solid_rgba(100, width, 2);
+        write_image(
+            path,
+            ImageFileFormat::Ppm,
+            &rgba,
+            PixelFormat::Rgba,
+            (width, 2),
+        )
+        .unwrap();
+    }
+
+    let pattern = folder.join("*.ppm").to_string_lossy().into_owned();
+    let mut capturer = ImageSequenceCapturer::new(BufferType::Frame, &pattern).unwrap();
+
+    let frames = capture_images(&mut capturer, 2).await;
+    assert_eq!(frames[0], Some(solid_rgba(100, 2, 2)));
+    assert_eq!(frames[1], None);
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[tokio::test]
+async fn test_image_sequence_capturer_raw_images() {
+    let folder = images_folder("raw");
+    std::fs::write(folder.join("a.raw"), solid_rgba(50, 2, 2)).unwrap();
+    std::fs::write(folder.join("b.raw"), solid_rgba(60, 2, 2)).unwrap();
+    std::fs::write(folder.join("c.raw"), solid_rgba(70, 2, 1)).unwrap();
+
+    let pattern = folder.join("*.raw").to_string_lossy().into_owned();
+
+    // The resolution of raw images is unknown until it is set
+    let mut capturer = ImageSequenceCapturer::new(BufferType::Frame, &pattern).unwrap();
+    assert_eq!(capturer.buffer_size(), 0);
+    assert_eq!(capture_images(&mut capturer, 1).await, vec![None]);
+
+    let mut capturer = ImageSequenceCapturer::new(BufferType::Frame, &pattern)
+        .unwrap()
+        .resolution(2, 2);
+    assert_eq!(capturer.buffer_size(), 16);
+    assert_eq!(
+        capture_images(&mut capturer, 3).await,
+        vec![Some(solid_rgba(50, 2, 2)), Some(solid_rgba(60, 2, 2)), None]
+    );
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+/// 3x2 BGRA frame whose rows are padded to 16 bytes, each pixel holding its coordinates
+fn padded_frame() -> Vec<u8> {
+    let mut frame = Vec::new();
+    for y in 0..2u8 {
+        for x in 0..3u8 {
+            frame.extend_from_slice(&[x, y, 0, 255]);
+        }
+        frame.extend_from_slice(&[0xEE; 4]);
+    }
+    frame
+}
+
+#[test]
+fn test_scrap_stride_removal() {
+    let mut output = BytesMut::new();
+    copy_region(&padded_frame(), 16, Rect::new(0, 0, 3, 2), &mut output);
+
+    assert_eq!(output.len(), 3 * 2 * 4);
+    assert!(!output.contains(&0xEE));
+    assert_eq!(&output[12..16], &[0, 1, 0, 255]);
+}
+
+#[test]
+fn test_scrap_region() {
+    let mut output = BytesMut::new();
+    copy_region(&padded_frame(), 16, Rect::new(1, 1, 2, 1), &mut output);
+    assert_eq!(&output[..], &[1, 1, 0, 255, 2, 1, 0, 255]);
+
+    let mut output = BytesMut::new();
+    copy_region(&padded_frame(), 16, Rect::new(2, 0, 1, 2), &mut output);
+    assert_eq!(&output[..], &[2, 0, 0, 255, 2, 1, 0, 255]);
+}
+
+#[tokio::test]
+async fn test_scrap_capture_retry() {
+    // Captures are retried while no new frame is ready
+    let mut attempts = 0;
+    let result = capture_with_retry(
+        || {
+            attempts += 1;
+            if attempts < 3 {
+                CaptureOutcome::WouldBlock
+            } else {
+                CaptureOutcome::Captured
+            }
+        },
+        None,
+        Duration::from_millis(1),
+    )
+    .await;
+    assert_eq!(result, Ok(()));
+    assert_eq!(attempts, 3);
+
+    let started_at = Instant::now();
+    let result = capture_with_retry(
+        || CaptureOutcome::WouldBlock,
+        Some(Duration::from_millis(20)),
+        Duration::from_millis(1),
+    )
+    .await;
+    assert_eq!(result, Err(DropReason::NoCapturedFrames));
+    assert!(started_at.elapsed() >= Duration::from_millis(20));
+
+    // A zero timeout does not wait
+    let mut attempts = 0;
+    let result = capture_with_retry(
+        || {
+            attempts += 1;
+            CaptureOutcome::WouldBlock
+        },
+        Some(Duration::ZERO),
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(result, Err(DropReason::NoCapturedFrames));
+    assert_eq!(attempts, 1);
+
+    let result = capture_with_retry(
+        || CaptureOutcome::Failed(std::io::Error::other("Display lost")),
+        None,
+        Duration::from_millis(1),
+    )
+    .await;
+    assert_eq!(result, Err(DropReason::CaptureError));
+}
+
+fn assert_processor<F, T: FrameProcessor<F>>() {}
+
+#[test]
+fn test_scrap_capturer_properties() {
+    // Frames only need a buffer unless a property or the error reporting is required
+    assert_processor::<BufferOnlyFrameData, ScrapFrameCapturer<BufferType>>();
+    assert_processor::<TestFrameData, ScrapFrameCapturer<BufferType, Option<Stat>>>();
+}
+
+#[test]
+fn test_scrap_display_index() {
+    // Whether or not displays are available, an out of range index is an error
+    assert!(ScrapFrameCapturer::new_from_display_index(BufferType::Frame, usize::MAX).is_err());
+}
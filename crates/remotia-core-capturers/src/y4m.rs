@@ -1,44 +1,289 @@
-use std::fs::File;
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use log::debug;
+use log::{debug, warn};
 use remotia_buffer_utils::{BufMut, BytesMut};
-use remotia_core::traits::{BorrowMutFrameProperties, FrameProcessor};
-use y4m::Decoder;
+use remotia_core::traits::{BorrowMutFrameProperties, FrameProcessor, OptionalPropertyKey};
+use remotia_image_utils::y4m::plane_sizes;
+use tokio::time::{Interval, MissedTickBehavior};
+use y4m::{Colorspace, Decoder, Ratio};
 
-pub struct Y4MFrameCapturer<K> {
+#[derive(Debug, Clone, Copy)]
+pub struct Y4MStreamInfo {
+    pub width: usize,
+    pub height: usize,
+    pub frame_rate: Ratio,
+    pub colorspace: Colorspace,
+}
+
+impl Y4MStreamInfo {
+    fn from_decoder(decoder: &Decoder<File>) -> Self {
+        Self {
+            width: decoder.get_width(),
+            height: decoder.get_height(),
+            frame_rate: decoder.get_framerate(),
+            colorspace: decoder.get_colorspace(),
+        }
+    }
+
+    /// Duration of a single frame according to the stream frame rate
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frame_rate.den as f64 / self.frame_rate.num as f64)
+    }
+
+    /// Presentation timestamp in milliseconds of the n-th frame of the stream
+    pub fn presentation_timestamp(&self, frame_index: u128) -> u128 {
+        frame_index * 1000 * self.frame_rate.den as u128 / self.frame_rate.num as u128
+    }
+
+    /// Size in bytes of the Y, U and V planes of a frame
+    pub fn plane_sizes(&self) -> (usize, usize, usize) {
+        plane_sizes(self.colorspace, self.width, self.height)
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        let (y_size, u_size, v_size) = self.plane_sizes();
+        y_size + u_size + v_size
+    }
+}
+
+/// Reads the frames of a Y4M file, writing the Y, U and V planes contiguously in the buffer.
+///
+/// Optionally publishes the index of the frame inside the file and its presentation timestamp
+/// (in milliseconds) as frame properties. Presentation timestamps keep increasing when looping,
+/// so that the capturer can stand in for a live source. Until one of their keys is set, the
+/// frames are not required to have any property besides the buffer.
+pub struct Y4MFrameCapturer<K, P = ()> {
+    path: PathBuf,
     stream: Decoder<File>,
+    info: Y4MStreamInfo,
+
     buffer_key: K,
+    frame_index_key: P,
+    timestamp_key: P,
+
+    start_frame: usize,
+    looping: bool,
+    realtime: bool,
+
+    interval: Option<Interval>,
+    current_frame: usize,
+    emitted_frames: u128,
 }
 
 impl<K> Y4MFrameCapturer<K> {
-    pub fn new(buffer_key: K, path: &str) -> Self {
-        Self {
-            stream: y4m::decode(File::open(path).unwrap()).unwrap(),
+    pub fn new<T: AsRef<Path>>(buffer_key: K, path: T) -> Result<Self, y4m::Error> {
+        let path = path.as_ref().to_path_buf();
+        let stream = open_stream(&path)?;
+        let info = Y4MStreamInfo::from_decoder(&stream);
+
+        if info.frame_rate.num == 0 || info.frame_rate.den == 0 {
+            warn!(
+                "Invalid frame rate {} of Y4M stream {:?}",
+                info.frame_rate, path
+            );
+            return Err(y4m::Error::BadInput);
+        }
+
+        debug!("Opened Y4M stream {:?}: {:?}", path, info);
+
+        Ok(Self {
+            path,
+            stream,
+            info,
             buffer_key,
+            frame_index_key: (),
+            timestamp_key: (),
+            start_frame: 0,
+            looping: false,
+            realtime: false,
+            interval: None,
+            current_frame: 0,
+            emitted_frames: 0,
+        })
+    }
+
+    pub fn frame_index_key<P>(self, key: P) -> Y4MFrameCapturer<K, Option<P>> {
+        self.with_keys(Some(key), None)
+    }
+
+    pub fn timestamp_key<P>(self, key: P) -> Y4MFrameCapturer<K, Option<P>> {
+        self.with_keys(None, Some(key))
+    }
+
+    fn with_keys<P>(self, frame_index_key: P, timestamp_key: P) -> Y4MFrameCapturer<K, P> {
+        Y4MFrameCapturer {
+            path: self.path,
+            stream: self.stream,
+            info: self.info,
+            buffer_key: self.buffer_key,
+            frame_index_key,
+            timestamp_key,
+            start_frame: self.start_frame,
+            looping: self.looping,
+            realtime: self.realtime,
+            interval: self.interval,
+            current_frame: self.current_frame,
+            emitted_frames: self.emitted_frames,
         }
     }
 }
 
+impl<K, P> Y4MFrameCapturer<K, Option<P>> {
+    pub fn frame_index_key(mut self, key: P) -> Self {
+        self.frame_index_key = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.timestamp_key = Some(key);
+        self
+    }
+}
+
+impl<K, P> Y4MFrameCapturer<K, P> {
+    /// Restarts from the starting frame instead of stopping when the end of the file is reached
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Paces reads to the native frame rate of the file
+    pub fn realtime(mut self) -> Self {
+        self.realtime = true;
+        self
+    }
+
+    /// Skips the frames preceding `frame_index`, which is also the frame the capturer restarts
+    /// from when looping
+    pub fn seek(mut self, frame_index: usize) -> Result<Self, y4m::Error> {
+        if frame_index < self.current_frame {
+            self.stream = open_stream(&self.path)?;
+            self.current_frame = 0;
+        }
+
+        while self.current_frame < frame_index {
+            self.stream.read_frame()?;
+            self.current_frame += 1;
+        }
+
+        self.start_frame = frame_index;
+        Ok(self)
+    }
+
+    pub fn info(&self) -> Y4MStreamInfo {
+        self.info
+    }
+
+    pub fn width(&self) -> usize {
+        self.info.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.info.height
+    }
+
+    pub fn frame_rate(&self) -> Ratio {
+        self.info.frame_rate
+    }
+
+    pub fn colorspace(&self) -> Colorspace {
+        self.info.colorspace
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.info.buffer_size()
+    }
+
+    fn rewind(&mut self) -> Result<(), y4m::Error> {
+        debug!("Rewinding Y4M stream to frame {}", self.start_frame);
+
+        self.stream = open_stream(&self.path)?;
+        self.current_frame = 0;
+
+        while self.current_frame < self.start_frame {
+            self.stream.read_frame()?;
+            self.current_frame += 1;
+        }
+
+        Ok(())
+    }
+
+    async fn wait_next_frame(&mut self) {
+        if !self.realtime {
+            return;
+        }
+
+        let frame_duration = self.info.frame_duration();
+        let interval = self.interval.get_or_insert_with(|| {
+            let mut interval = tokio::time::interval(frame_duration);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+
+        interval.tick().await;
+    }
+}
+
+fn open_stream(path: &Path) -> Result<Decoder<File>, y4m::Error> {
+    y4m::decode(File::open(path)?)
+}
+
+fn read_frame_into(stream: &mut Decoder<File>, buffer: &mut BytesMut) -> Result<(), y4m::Error> {
+    let frame = stream.read_frame()?;
+    buffer.put(frame.get_y_plane());
+    buffer.put(frame.get_u_plane());
+    buffer.put(frame.get_v_plane());
+    Ok(())
+}
+
 #[async_trait]
-impl<F, K> FrameProcessor<F> for Y4MFrameCapturer<K>
+impl<F, K, P> FrameProcessor<F> for Y4MFrameCapturer<K, P>
 where
     K: Send,
+    P: OptionalPropertyKey<F, u128>,
     F: BorrowMutFrameProperties<K, BytesMut> + Send + 'static,
 {
     async fn process(&mut self, mut frame_data: F) -> Option<F> {
-        let frame = self.stream.read_frame();
-        if frame.is_err() {
-            debug!("No more frames to extract");
-            return None;
+        self.wait_next_frame().await;
+
+        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+
+        let mut result = read_frame_into(&mut self.stream, buffer);
+        if let (Err(y4m::Error::EOF), true) = (&result, self.looping) {
+            result = self
+                .rewind()
+                .and_then(|_| read_frame_into(&mut self.stream, buffer));
         }
 
-        let frame = frame.unwrap();
+        match result {
+            Ok(()) => {}
+            Err(y4m::Error::EOF) => {
+                debug!("No more frames to extract");
+                return None;
+            }
+            Err(error) => {
+                debug!("Unable to read Y4M frame: {}", error);
+                return None;
+            }
+        }
 
-        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
-        buffer.put(frame.get_y_plane());
-        buffer.put(frame.get_u_plane());
-        buffer.put(frame.get_v_plane());
+        // Timestamps start from the one of the starting frame, as if the file had been played
+        // from its beginning
+        let timestamp = self
+            .info
+            .presentation_timestamp(self.start_frame as u128 + self.emitted_frames);
+
+        self.frame_index_key
+            .set_property(&mut frame_data, self.current_frame as u128);
+        self.timestamp_key.set_property(&mut frame_data, timestamp);
+
+        self.current_frame += 1;
+        self.emitted_frames += 1;
 
         Some(frame_data)
     }
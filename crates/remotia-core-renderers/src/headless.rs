@@ -0,0 +1,218 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    common::helpers::time::now_timestamp,
+    traits::{BorrowFrameProperties, FrameProcessor, OptionalPropertyKey},
+};
+
+/// Discards frames, counting them and optionally writing the time they reached the sink.
+/// Until the timestamp key is set, the frames are not required to have any property.
+pub struct NullSink<P = ()> {
+    timestamp_key: P,
+    counter: Arc<AtomicUsize>,
+}
+
+impl Default for NullSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NullSink {
+    pub fn new() -> Self {
+        Self {
+            timestamp_key: (),
+            counter: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Sets the key of the property the render timestamp is written to
+    pub fn timestamp_key<P>(self, key: P) -> NullSink<Option<P>> {
+        NullSink {
+            timestamp_key: Some(key),
+            counter: self.counter,
+        }
+    }
+}
+
+impl<P> NullSink<P> {
+    /// Shared handle to the number of received frames, still readable after the sink is moved
+    /// into a pipeline
+    pub fn counter(&self) -> Arc<AtomicUsize> {
+        self.counter.clone()
+    }
+}
+
+#[async_trait]
+impl<F, P> FrameProcessor<F> for NullSink<P>
+where
+    P: OptionalPropertyKey<F, u128>,
+    F: Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let count = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        debug!("Discarding frame #{}", count);
+
+        self.timestamp_key
+            .set_property(&mut frame_data, now_timestamp());
+
+        Some(frame_data)
+    }
+}
+
+/// Handle to the frames retained by a `MemorySink`, from the oldest to the most recent
+#[derive(Clone, Default)]
+pub struct RetainedFrames {
+    frames: Arc<Mutex<VecDeque<Vec<u8>>>>,
+}
+
+impl RetainedFrames {
+    pub fn len(&self) -> usize {
+        self.frames.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.lock().unwrap().is_empty()
+    }
+
+    pub fn last(&self) -> Option<Vec<u8>> {
+        self.frames.lock().unwrap().back().cloned()
+    }
+
+    pub fn snapshot(&self) -> Vec<Vec<u8>> {
+        self.frames.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.frames.lock().unwrap().clear();
+    }
+}
+
+/// Copies the content of a buffer, retaining the last `capacity` frames
+pub struct MemorySink<K> {
+    buffer_key: K,
+    capacity: usize,
+    frames: RetainedFrames,
+}
+
+impl<K> MemorySink<K> {
+    pub fn new(buffer_key: K, capacity: usize) -> Self {
+        Self {
+            buffer_key,
+            capacity,
+            frames: RetainedFrames::default(),
+        }
+    }
+
+    pub fn frames(&self) -> RetainedFrames {
+        self.frames.clone()
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for MemorySink<K>
+where
+    K: Send,
+    F: BorrowFrameProperties<K, BytesMut> + Send + 'static,
+{
+    async fn process(&mut self, frame_data: F) -> Option<F> {
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        let mut frames = self.frames.frames.lock().unwrap();
+        if self.capacity > 0 {
+            if frames.len() == self.capacity {
+                frames.pop_front();
+            }
+            frames.push_back(buffer.to_vec());
+        }
+        drop(frames);
+
+        Some(frame_data)
+    }
+}
+
+/// 64-bit FNV-1a hash of a buffer
+pub fn checksum(buffer: &[u8]) -> u64 {
+    buffer.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Hashes the content of a buffer, recording the checksums of the last `capacity` frames
+/// and optionally writing them to a frame property
+pub struct ChecksumSink<K, P = ()> {
+    buffer_key: K,
+    checksum_key: P,
+    capacity: usize,
+    checksums: Arc<Mutex<VecDeque<u64>>>,
+}
+
+impl<K> ChecksumSink<K> {
+    pub fn new(buffer_key: K) -> Self {
+        Self {
+            buffer_key,
+            checksum_key: (),
+            capacity: 1024,
+            checksums: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn checksum_key<P>(self, key: P) -> ChecksumSink<K, Option<P>> {
+        ChecksumSink {
+            buffer_key: self.buffer_key,
+            checksum_key: Some(key),
+            capacity: self.capacity,
+            checksums: self.checksums,
+        }
+    }
+}
+
+impl<K, P> ChecksumSink<K, P> {
+    /// Sets how many checksums are retained, dropping the oldest ones (1024 by default)
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Shared handle to the checksums of the last received frames, in order of arrival
+    pub fn checksums(&self) -> Arc<Mutex<VecDeque<u64>>> {
+        self.checksums.clone()
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for ChecksumSink<K, P>
+where
+    K: Send,
+    P: OptionalPropertyKey<F, u128>,
+    F: BorrowFrameProperties<K, BytesMut> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+        let checksum = checksum(buffer);
+        debug!("Frame checksum: {:016x}", checksum);
+
+        let mut checksums = self.checksums.lock().unwrap();
+        if self.capacity > 0 {
+            if checksums.len() == self.capacity {
+                checksums.pop_front();
+            }
+            checksums.push_back(checksum);
+        }
+        drop(checksums);
+
+        self.checksum_key
+            .set_property(&mut frame_data, checksum as u128);
+
+        Some(frame_data)
+    }
+}
@@ -1 +1,6 @@
+pub mod headless;
 pub mod winit;
+pub mod y4m;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,235 @@
+use std::{collections::HashMap, sync::atomic::Ordering};
+
+use remotia_buffer_utils::BytesMut;
+use remotia_core::traits::{
+    BorrowFrameProperties, BorrowMutFrameProperties, FrameProcessor, FrameProperties,
+};
+use remotia_core_capturers::y4m::Y4MFrameCapturer;
+use remotia_image_utils::{convert::convert_frame, format::PixelFormat};
+use y4m::Colorspace;
+
+use crate::{
+    headless::{checksum, ChecksumSink, MemorySink, NullSink},
+    winit::WinitRenderer,
+    y4m::Y4MFrameWriter,
+};
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+enum BufferType {
+    Frame,
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+enum Stat {
+    RenderTime,
+    Checksum,
+}
+
+#[derive(Default)]
+struct TestFrameData {
+    buffers: HashMap<BufferType, BytesMut>,
+    stats: HashMap<Stat, u128>,
+}
+
+impl BorrowFrameProperties<BufferType, BytesMut> for TestFrameData {
+    fn get_ref(&self, key: &BufferType) -> Option<&BytesMut> {
+        self.buffers.get(key)
+    }
+}
+
+impl BorrowMutFrameProperties<BufferType, BytesMut> for TestFrameData {
+    fn get_mut_ref(&mut self, key: &BufferType) -> Option<&mut BytesMut> {
+        self.buffers.get_mut(key)
+    }
+}
+
+impl FrameProperties<Stat, u128> for TestFrameData {
+    fn set(&mut self, key: Stat, value: u128) {
+        self.stats.insert(key, value);
+    }
+
+    fn get(&self, key: &Stat) -> Option<u128> {
+        self.stats.get(key).copied()
+    }
+}
+
+fn frame(content: &[u8]) -> TestFrameData {
+    let mut frame_data = TestFrameData::default();
+    frame_data
+        .buffers
+        .insert(BufferType::Frame, BytesMut::from(content));
+    frame_data
+}
+
+#[tokio::test]
+async fn test_null_sink() {
+    let mut sink = NullSink::new().timestamp_key(Stat::RenderTime);
+    let counter = sink.counter();
+
+    for _ in 0..3 {
+        let frame_data = sink.process(frame(&[0])).await.unwrap();
+        assert!(frame_data.get(&Stat::RenderTime).unwrap() > 0);
+    }
+
+    assert_eq!(counter.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test]
+async fn test_memory_sink_retains_last_frames() {
+    let mut sink = MemorySink::new(BufferType::Frame, 2);
+    let frames = sink.frames();
+
+    for value in 0..5 {
+        sink.process(frame(&[value])).await.unwrap();
+    }
+
+    assert_eq!(frames.snapshot(), vec![vec![3], vec![4]]);
+    assert_eq!(frames.last(), Some(vec![4]));
+}
+
+#[tokio::test]
+async fn test_checksum_sink() {
+    let mut sink = ChecksumSink::new(BufferType::Frame).checksum_key(Stat::Checksum);
+    let checksums = sink.checksums();
+
+    let frame_data = sink.process(frame(b"a")).await.unwrap();
+    sink.process(frame(b"a")).await.unwrap();
+    sink.process(frame(b"b")).await.unwrap();
+
+    assert_eq!(checksum(b"a"), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(
+        frame_data.get(&Stat::Checksum),
+        Some(checksum(b"a") as u128)
+    );
+
+    let checksums = checksums.lock().unwrap();
+    assert_eq!(checksums[0], checksums[1]);
+    assert_ne!(checksums[1], checksums[2]);
+}
+
+#[tokio::test]
+async fn test_headless_sinks_without_properties() {
+    let mut null_sink = NullSink::new();
+    let counter = null_sink.counter();
+    null_sink
+        .process(BufferOnlyFrameData(BytesMut::from(&b"a"[..])))
+        .await
+        .unwrap();
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    let mut checksum_sink = ChecksumSink::new(BufferType::Frame).capacity(2);
+    let checksums = checksum_sink.checksums();
+    for content in [b"a", b"b", b"c"] {
+        checksum_sink
+            .process(BufferOnlyFrameData(BytesMut::from(&content[..])))
+            .await
+            .unwrap();
+    }
+
+    let checksums: Vec<u64> = checksums.lock().unwrap().iter().copied().collect();
+    assert_eq!(checksums, vec![checksum(b"b"), checksum(b"c")]);
+}
+
+fn y4m_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "remotia-renderers-{}-{}.y4m",
+        name,
+        std::process::id()
+    ))
+}
+
+async fn read_y4m(path: &std::path::Path) -> Vec<Vec<u8>> {
+    let mut capturer = Y4MFrameCapturer::new(BufferType::Frame, path).unwrap();
+
+    let mut frames = Vec::new();
+    while let Some(frame_data) = capturer.process(frame(&[])).await {
+        frames.push(frame_data.buffers[&BufferType::Frame].to_vec());
+    }
+    frames
+}
+
+#[tokio::test]
+async fn test_y4m_writer_round_trip() {
+    let path = y4m_path("planar");
+    let frames: Vec<Vec<u8>> = (0..3u8)
+        .map(|index| (0..12).map(|sample| index * 16 + sample).collect())
+        .collect();
+
+    let mut writer = Y4MFrameWriter::new(BufferType::Frame, &path, 4, 2)
+        .unwrap()
+        .frame_rate(30, 1);
+    for content in &frames {
+        writer.process(frame(content)).await.unwrap();
+    }
+    drop(writer);
+
+    let capturer = Y4MFrameCapturer::new(BufferType::Frame, &path).unwrap();
+    assert_eq!((capturer.width(), capturer.height()), (4, 2));
+    assert_eq!(capturer.frame_rate().num, 30);
+    assert!(matches!(capturer.colorspace(), Colorspace::C420));
+
+    assert_eq!(read_y4m(&path).await, frames);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_y4m_writer_conversion_round_trip() {
+    let path = y4m_path("converted");
+    let rgba: Vec<u8> = (0..4 * 2)
+        .flat_map(|pixel| [pixel * 30, 255 - pixel * 30, 128, 255])
+        .collect();
+
+    let mut writer = Y4MFrameWriter::new(BufferType::Frame, &path, 4, 2)
+        .unwrap()
+        .convert_from(PixelFormat::Rgba);
+    writer.process(frame(&rgba)).await.unwrap();
+    drop(writer);
+
+    let mut expected = vec![0; PixelFormat::Yuv420p.buffer_size(4, 2)];
+    convert_frame(
+        &rgba,
+        PixelFormat::Rgba,
+        &mut expected,
+        PixelFormat::Yuv420p,
+        (4, 2),
+    );
+
+    assert_eq!(read_y4m(&path).await, vec![expected]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Unable to convert")]
+fn test_y4m_writer_rejects_unconvertible_colorspace() {
+    let path = y4m_path("unconvertible");
+    let _ = Y4MFrameWriter::<BufferType>::new(BufferType::Frame, &path, 4, 2)
+        .unwrap()
+        .convert_from(PixelFormat::Rgba)
+        .colorspace(Colorspace::C444);
+}
+
+/// Frame without any property besides its buffer
+struct BufferOnlyFrameData(BytesMut);
+
+impl BorrowMutFrameProperties<BufferType, BytesMut> for BufferOnlyFrameData {
+    fn get_mut_ref(&mut self, _key: &BufferType) -> Option<&mut BytesMut> {
+        Some(&mut self.0)
+    }
+}
+
+impl BorrowFrameProperties<BufferType, BytesMut> for BufferOnlyFrameData {
+    fn get_ref(&self, _key: &BufferType) -> Option<&BytesMut> {
+        Some(&self.0)
+    }
+}
+
+fn assert_processor<F, T: FrameProcessor<F>>() {}
+
+#[test]
+fn test_winit_renderer_properties() {
+    // The resolution properties are only required once their keys are set
+    assert_processor::<BufferOnlyFrameData, WinitRenderer<BufferType>>();
+    assert_processor::<TestFrameData, WinitRenderer<BufferType, Option<Stat>>>();
+}
@@ -1,71 +1,301 @@
 use std::sync::{Arc, Mutex};
 
+use log::{debug, info, warn};
 use pixels::{Pixels, SurfaceTexture};
 use remotia_buffer_utils::BytesMut;
-use remotia_core::traits::{BorrowMutFrameProperties, FrameProcessor};
+use remotia_core::traits::{BorrowMutFrameProperties, FrameProcessor, OptionalPropertyKey};
+use remotia_input_utils::events::{InputEvent, InputEventKind, PointerButton};
 
 use async_trait::async_trait;
-use winit::{event_loop::EventLoop, window::WindowBuilder};
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    watch,
+};
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    event_loop::EventLoop,
+    platform::scancode::PhysicalKeyExtScancode,
+    window::{Window, WindowBuilder},
+};
 
-pub struct WinitRenderer<'a, K> {
+/// Renders RGBA frames to a window, scaling them to fit its surface.
+///
+/// Buffers whose size does not match the current frame resolution are skipped, unless the
+/// resolution keys are set, in which case the pixel buffer follows the resolution written in the
+/// frame properties. Until then, the frames are not required to have any property besides the
+/// buffer.
+pub struct WinitRenderer<'a, K, P = ()> {
     buffer_key: K,
+    width_key: P,
+    height_key: P,
+
     pixels: Option<Arc<Mutex<Pixels<'a>>>>,
+    window: Option<Arc<Window>>,
+    resolution: (u32, u32),
+    closed: Option<watch::Receiver<bool>>,
 }
 
 impl<'a, K> WinitRenderer<'a, K> {
     pub fn new(buffer_key: K) -> Self {
         Self {
             buffer_key,
+            width_key: (),
+            height_key: (),
             pixels: None,
+            window: None,
+            resolution: (0, 0),
+            closed: None,
         }
     }
 
+    /// Sets the keys of the properties containing the width and height of each frame
+    pub fn resolution_keys<P>(
+        self,
+        width_key: P,
+        height_key: P,
+    ) -> WinitRenderer<'a, K, Option<P>> {
+        WinitRenderer {
+            buffer_key: self.buffer_key,
+            width_key: Some(width_key),
+            height_key: Some(height_key),
+            pixels: self.pixels,
+            window: self.window,
+            resolution: self.resolution,
+            closed: self.closed,
+        }
+    }
+}
+
+impl<'a, K, P> WinitRenderer<'a, K, P> {
     pub fn allocate(&mut self, width: u32, height: u32) -> WinitRunner<'a> {
         let event_loop = EventLoop::new().unwrap();
 
-        let window = { WindowBuilder::new().build(&event_loop).unwrap() };
+        let window = Arc::new(
+            WindowBuilder::new()
+                .with_title("remotia")
+                .with_inner_size(PhysicalSize::new(width, height))
+                .build(&event_loop)
+                .unwrap(),
+        );
 
         let pixels = Arc::new(Mutex::new({
-            let surface_texture = SurfaceTexture::new(width, height, window);
+            let surface_size = window.inner_size();
+            let surface_texture =
+                SurfaceTexture::new(surface_size.width, surface_size.height, window.clone());
             Pixels::new(width, height, surface_texture).unwrap()
         }));
 
+        let (close_sender, close_receiver) = watch::channel(false);
+        let (events_sender, events_receiver) = mpsc::unbounded_channel();
+
         self.pixels = Some(pixels.clone());
+        self.window = Some(window.clone());
+        self.resolution = (width, height);
+        self.closed = Some(close_receiver);
+
+        WinitRunner {
+            event_loop,
+            window,
+            pixels,
+            close_sender,
+            events_sender,
+            events_receiver: Some(events_receiver),
+        }
+    }
 
-        WinitRunner { event_loop, pixels }
+    fn is_closed(&self) -> bool {
+        self.closed
+            .as_ref()
+            .map(|closed| *closed.borrow())
+            .unwrap_or(false)
     }
 }
 
 pub struct WinitRunner<'a> {
     event_loop: EventLoop<()>,
+    window: Arc<Window>,
     pixels: Arc<Mutex<Pixels<'a>>>,
+
+    close_sender: watch::Sender<bool>,
+    events_sender: UnboundedSender<InputEvent>,
+    events_receiver: Option<UnboundedReceiver<InputEvent>>,
 }
 
 unsafe impl<'a> Send for WinitRunner<'a> {}
 
 impl<'a> WinitRunner<'a> {
+    /// Receiver that turns `true` when the window is closed, to be used to stop the pipelines
+    pub fn close_signal(&self) -> watch::Receiver<bool> {
+        self.close_sender.subscribe()
+    }
+
+    /// Takes the stream of keyboard and mouse events received by the window, with pointer
+    /// positions mapped to the pixels of the rendered frame
+    pub fn take_input_events(&mut self) -> Option<UnboundedReceiver<InputEvent>> {
+        self.events_receiver.take()
+    }
+
+    /// Runs the event loop on the current thread, returning when the window is closed
     pub fn start(self) {
-        self.event_loop
-            .run(|_event, _elwt| {
-                self.pixels.lock().unwrap().render().unwrap();
-                log::debug!("Rendering buffer...")
+        let Self {
+            event_loop,
+            window,
+            pixels,
+            close_sender,
+            events_sender,
+            events_receiver,
+        } = self;
+
+        // Events are not forwarded if nobody took the stream
+        let forward_events = events_receiver.is_none();
+        drop(events_receiver);
+
+        event_loop
+            .run(move |event, elwt| {
+                let event = match event {
+                    Event::WindowEvent { window_id, event } if window_id == window.id() => event,
+                    _ => return,
+                };
+
+                match event {
+                    WindowEvent::CloseRequested => {
+                        info!("Window closed");
+                        close_sender.send_replace(true);
+                        elwt.exit();
+                    }
+                    WindowEvent::Resized(size) => {
+                        debug!("Resizing surface to {}x{}", size.width, size.height);
+                        if let Err(error) = pixels
+                            .lock()
+                            .unwrap()
+                            .resize_surface(size.width, size.height)
+                        {
+                            warn!("Unable to resize surface: {}", error);
+                        }
+                        window.request_redraw();
+                    }
+                    WindowEvent::RedrawRequested => {
+                        log::debug!("Rendering buffer...");
+                        if let Err(error) = pixels.lock().unwrap().render() {
+                            warn!("Rendering error: {}", error);
+                            close_sender.send_replace(true);
+                            elwt.exit();
+                        }
+                    }
+                    event if forward_events => {
+                        let pixels = pixels.lock().unwrap();
+                        if let Some(kind) = input_event_kind(&event, &pixels) {
+                            events_sender
+                                .send(InputEvent::new(kind))
+                                .unwrap_or_else(|_| debug!("Input events receiver dropped"));
+                        }
+                    }
+                    _ => {}
+                }
             })
             .unwrap();
     }
 }
 
+/// Scroll distance in pixels of a line, for devices reporting pixel deltas
+const SCROLL_LINE_HEIGHT: f64 = 20.0;
+
+fn input_event_kind(event: &WindowEvent, pixels: &Pixels) -> Option<InputEventKind> {
+    let kind = match event {
+        WindowEvent::KeyboardInput { event, .. } => {
+            let key_code = event.physical_key.to_scancode()?;
+            match event.state {
+                ElementState::Pressed => InputEventKind::KeyDown { key_code },
+                ElementState::Released => InputEventKind::KeyUp { key_code },
+            }
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+            let (x, y) = pixels
+                .window_pos_to_pixel((position.x as f32, position.y as f32))
+                .unwrap_or_else(|position| pixels.clamp_pixel_pos(position));
+            InputEventKind::PointerMove {
+                x: x as f32,
+                y: y as f32,
+            }
+        }
+        WindowEvent::MouseInput { state, button, .. } => InputEventKind::PointerButton {
+            button: match button {
+                MouseButton::Left => PointerButton::Left,
+                MouseButton::Right => PointerButton::Right,
+                MouseButton::Middle => PointerButton::Middle,
+                MouseButton::Back => PointerButton::Back,
+                MouseButton::Forward => PointerButton::Forward,
+                MouseButton::Other(code) => PointerButton::Other((*code).min(u8::MAX as u16) as u8),
+            },
+            pressed: *state == ElementState::Pressed,
+        },
+        WindowEvent::MouseWheel { delta, .. } => {
+            let (delta_x, delta_y) = match delta {
+                MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+                MouseScrollDelta::PixelDelta(position) => (
+                    (position.x / SCROLL_LINE_HEIGHT) as f32,
+                    (position.y / SCROLL_LINE_HEIGHT) as f32,
+                ),
+            };
+            InputEventKind::PointerScroll { delta_x, delta_y }
+        }
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
 #[async_trait]
-impl<'a, F, K> FrameProcessor<F> for WinitRenderer<'a, K>
+impl<'a, F, K, P> FrameProcessor<F> for WinitRenderer<'a, K, P>
 where
     K: Send,
+    P: OptionalPropertyKey<F, u128>,
     F: BorrowMutFrameProperties<K, BytesMut> + Send + 'static,
 {
     async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        if self.is_closed() {
+            debug!("Window closed, dropping frame");
+            return None;
+        }
+
         log::debug!("Filling pixels buffer...");
 
+        let resolution = self
+            .width_key
+            .get_property(&frame_data)
+            .zip(self.height_key.get_property(&frame_data))
+            .map(|(width, height)| (width as u32, height as u32));
+
         let raw_frame_buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
         let mut pixels = self.pixels.as_mut().unwrap().lock().unwrap();
-        pixels.frame_mut().copy_from_slice(raw_frame_buffer);
+
+        if let Some((width, height)) = resolution {
+            if (width, height) != self.resolution {
+                debug!("Resizing pixels buffer to {}x{}", width, height);
+                match pixels.resize_buffer(width, height) {
+                    Ok(()) => self.resolution = (width, height),
+                    Err(error) => warn!("Unable to resize pixels buffer: {}", error),
+                }
+            }
+        }
+
+        let frame = pixels.frame_mut();
+        if frame.len() != raw_frame_buffer.len() {
+            warn!(
+                "Skipping frame of {} bytes, expected {} bytes for a {}x{} frame",
+                raw_frame_buffer.len(),
+                frame.len(),
+                self.resolution.0,
+                self.resolution.1
+            );
+            return Some(frame_data);
+        }
+
+        frame.copy_from_slice(raw_frame_buffer);
+        drop(pixels);
+
+        self.window.as_ref().unwrap().request_redraw();
 
         Some(frame_data)
     }
@@ -0,0 +1,167 @@
+use std::{fs::File, path::Path};
+
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::traits::{BorrowFrameProperties, FrameProcessor};
+use remotia_image_utils::{
+    convert::convert_frame,
+    format::PixelFormat,
+    y4m::{pixel_format, plane_sizes},
+};
+use y4m::{Colorspace, Encoder, Ratio};
+
+/// Writes the frames contained in a buffer to a Y4M file.
+///
+/// Buffers are expected to contain the Y, U and V planes contiguously, laid out according to the
+/// configured colorspace. Packed RGBA/BGRA frames may be written as well by setting the format to
+/// convert from, in which case the colorspace must be an 8-bit 4:2:0 one.
+pub struct Y4MFrameWriter<K> {
+    buffer_key: K,
+
+    writer: Option<File>,
+    encoder: Option<Encoder<File>>,
+
+    width: usize,
+    height: usize,
+    frame_rate: Ratio,
+    colorspace: Colorspace,
+
+    source_format: Option<PixelFormat>,
+    conversion_buffer: Vec<u8>,
+}
+
+impl<K> Y4MFrameWriter<K> {
+    pub fn new<T: AsRef<Path>>(
+        buffer_key: K,
+        path: T,
+        width: usize,
+        height: usize,
+    ) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            buffer_key,
+            writer: Some(File::create(path)?),
+            encoder: None,
+            width,
+            height,
+            frame_rate: Ratio::new(60, 1),
+            colorspace: Colorspace::C420,
+            source_format: None,
+            conversion_buffer: Vec::new(),
+        })
+    }
+
+    pub fn frame_rate(mut self, num: usize, den: usize) -> Self {
+        self.frame_rate = Ratio::new(num, den);
+        self
+    }
+
+    pub fn colorspace(mut self, colorspace: Colorspace) -> Self {
+        self.colorspace = colorspace;
+        self.assert_convertible();
+        self
+    }
+
+    /// Converts packed RGBA/BGRA buffers to the YUV format of the colorspace before writing them
+    pub fn convert_from(mut self, format: PixelFormat) -> Self {
+        self.source_format = Some(format);
+        self.assert_convertible();
+        self
+    }
+
+    fn assert_convertible(&self) {
+        if let Some(source_format) = self.source_format {
+            assert!(
+                pixel_format(self.colorspace).is_some(),
+                "Unable to convert {:?} frames to the {:?} colorspace",
+                source_format,
+                self.colorspace
+            );
+        }
+    }
+
+    fn encoder(&mut self) -> &mut Encoder<File> {
+        if self.encoder.is_none() {
+            debug!(
+                "Writing Y4M header ({}x{}, {} fps, {:?})",
+                self.width, self.height, self.frame_rate, self.colorspace
+            );
+
+            let writer = self.writer.take().unwrap();
+            let encoder = y4m::encode(self.width, self.height, self.frame_rate)
+                .with_colorspace(self.colorspace)
+                .write_header(writer)
+                .unwrap_or_else(|error| panic!("Unable to write Y4M header: {}", error));
+
+            self.encoder = Some(encoder);
+        }
+
+        self.encoder.as_mut().unwrap()
+    }
+
+    fn write(&mut self, buffer: &[u8]) {
+        let (y_size, u_size, v_size) = plane_sizes(self.colorspace, self.width, self.height);
+
+        let mut conversion_buffer = std::mem::take(&mut self.conversion_buffer);
+        let planes = match self.source_format {
+            Some(source_format) => {
+                conversion_buffer.resize(y_size + u_size + v_size, 0);
+                convert_frame(
+                    buffer,
+                    source_format,
+                    &mut conversion_buffer,
+                    pixel_format(self.colorspace).unwrap(),
+                    (self.width, self.height),
+                );
+                &conversion_buffer[..]
+            }
+            None => buffer,
+        };
+
+        assert!(
+            planes.len() >= y_size + u_size + v_size,
+            "Frame buffer is too small for a {}x{} {:?} frame ({} bytes)",
+            self.width,
+            self.height,
+            self.colorspace,
+            planes.len()
+        );
+
+        let frame = y4m::Frame::new(
+            [
+                &planes[..y_size],
+                &planes[y_size..y_size + u_size],
+                &planes[y_size + u_size..y_size + u_size + v_size],
+            ],
+            None,
+        );
+
+        let encoder = self.encoder();
+        encoder
+            .write_frame(&frame)
+            .unwrap_or_else(|error| panic!("Unable to write Y4M frame: {}", error));
+
+        self.conversion_buffer = conversion_buffer;
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for Y4MFrameWriter<K>
+where
+    K: Send,
+    F: BorrowFrameProperties<K, BytesMut> + Send + 'static,
+{
+    async fn process(&mut self, frame_data: F) -> Option<F> {
+        debug!("Writing Y4M frame...");
+
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+        self.write(buffer);
+
+        Some(frame_data)
+    }
+}
@@ -0,0 +1,289 @@
+//! Broadcast of the frames of one pipeline to many TCP clients, each one receiving them as sent
+//! by a `TcpFrameSender`.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+
+use crate::{
+    connection::ErrorBackoff,
+    framing::{FrameHeader, HeaderKeys},
+};
+
+pub type ClientId = u64;
+
+/// What to do with a client whose queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowClientPolicy {
+    /// Skips the frames not fitting the queue
+    DropFrames,
+
+    /// Closes the connection
+    Disconnect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaveReason {
+    Disconnected,
+    TooSlow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientEvent {
+    Joined(ClientId, SocketAddr),
+    Left(ClientId, LeaveReason),
+}
+
+struct Client {
+    queue: mpsc::Sender<Arc<Vec<u8>>>,
+    dropped_frames: usize,
+}
+
+struct Clients {
+    clients: Mutex<HashMap<ClientId, Client>>,
+    events: broadcast::Sender<ClientEvent>,
+}
+
+impl Clients {
+    fn remove(&self, client_id: ClientId, reason: LeaveReason) {
+        if self.clients.lock().unwrap().remove(&client_id).is_some() {
+            info!("Client {} left ({:?})", client_id, reason);
+            self.events.send(ClientEvent::Left(client_id, reason)).ok();
+        }
+    }
+}
+
+/// Accepts any number of clients, sending each frame to all of them through per-client queues,
+/// so that a slow client does not hold back the others nor the pipeline.
+///
+/// Clients are accepted from the first processed frame on, the ones connecting earlier waiting
+/// in the backlog of the listener.
+pub struct TcpBroadcastSender<K, P> {
+    buffer_key: K,
+    header_keys: HeaderKeys<Option<P>>,
+
+    listener: Option<TcpListener>,
+    local_addr: SocketAddr,
+    queue_size: usize,
+    slow_client_policy: SlowClientPolicy,
+
+    clients: Arc<Clients>,
+    accept_task: Option<JoinHandle<()>>,
+}
+
+impl<K, P> TcpBroadcastSender<K, P> {
+    pub async fn bind(buffer_key: K, address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let local_addr = listener.local_addr()?;
+
+        let clients = Arc::new(Clients {
+            clients: Mutex::new(HashMap::new()),
+            events: broadcast::channel(64).0,
+        });
+
+        Ok(Self {
+            buffer_key,
+            header_keys: HeaderKeys::default(),
+            listener: Some(listener),
+            local_addr,
+            queue_size: 8,
+            slow_client_policy: SlowClientPolicy::DropFrames,
+            clients,
+            accept_task: None,
+        })
+    }
+
+    /// Sets how many frames can be waiting to be sent to each client (8 by default)
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        assert!(queue_size > 0, "Queue size must be positive");
+        self.queue_size = queue_size;
+        self
+    }
+
+    /// Sets what to do with the clients not keeping up (dropping their frames by default)
+    pub fn slow_client_policy(mut self, policy: SlowClientPolicy) -> Self {
+        self.slow_client_policy = policy;
+        self
+    }
+
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Receiver of the join and leave events of the clients
+    pub fn client_events(&self) -> broadcast::Receiver<ClientEvent> {
+        self.clients.events.subscribe()
+    }
+
+    pub fn clients_count(&self) -> usize {
+        self.clients.clients.lock().unwrap().len()
+    }
+
+    /// Number of frames skipped for each connected client
+    pub fn dropped_frames(&self) -> HashMap<ClientId, usize> {
+        self.clients
+            .clients
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(client_id, client)| (*client_id, client.dropped_frames))
+            .collect()
+    }
+}
+
+impl<K, P> Drop for TcpBroadcastSender<K, P> {
+    fn drop(&mut self) {
+        if let Some(accept_task) = &self.accept_task {
+            accept_task.abort();
+        }
+
+        // Closing the queues terminates the tasks sending to the clients
+        self.clients.clients.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for TcpBroadcastSender<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        if let Some(listener) = self.listener.take() {
+            self.accept_task = Some(tokio::spawn(accept_clients(
+                listener,
+                self.queue_size,
+                self.clients.clone(),
+            )));
+        }
+
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        let mut header = match FrameHeader::new(buffer.len()) {
+            Ok(header) => header,
+            Err(_) => {
+                warn!("Frame of {} bytes is too large to be sent", buffer.len());
+                frame_data.report_error(DropReason::OversizedFrame);
+                return Some(frame_data);
+            }
+        };
+        self.header_keys.fill(&mut header, &frame_data);
+
+        let mut packet = Vec::with_capacity(FrameHeader::SIZE + buffer.len());
+        packet.extend_from_slice(&header.encode());
+        packet.extend_from_slice(buffer);
+        let packet = Arc::new(packet);
+
+        let mut slow_clients = Vec::new();
+        for (client_id, client) in self.clients.clients.lock().unwrap().iter_mut() {
+            match client.queue.try_send(packet.clone()) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => match self.slow_client_policy {
+                    SlowClientPolicy::DropFrames => {
+                        debug!("Dropping frame for slow client {}", client_id);
+                        client.dropped_frames += 1;
+                    }
+                    SlowClientPolicy::Disconnect => slow_clients.push(*client_id),
+                },
+                Err(mpsc::error::TrySendError::Closed(_)) => {}
+            }
+        }
+
+        for client_id in slow_clients {
+            self.clients.remove(client_id, LeaveReason::TooSlow);
+        }
+
+        Some(frame_data)
+    }
+}
+
+async fn accept_clients(listener: TcpListener, queue_size: usize, clients: Arc<Clients>) {
+    let mut next_client_id: ClientId = 0;
+    let mut errors = ErrorBackoff::default();
+
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => {
+                errors.reset();
+                accepted
+            }
+            Err(error) => {
+                warn!("Unable to accept client: {}", error);
+                errors.wait().await;
+                continue;
+            }
+        };
+
+        let client_id = next_client_id;
+        next_client_id += 1;
+
+        let (queue, frames) = mpsc::channel(queue_size);
+        clients.clients.lock().unwrap().insert(
+            client_id,
+            Client {
+                queue,
+                dropped_frames: 0,
+            },
+        );
+
+        info!("Client {} joined from {}", client_id, address);
+        clients
+            .events
+            .send(ClientEvent::Joined(client_id, address))
+            .ok();
+
+        tokio::spawn(send_frames(stream, frames, client_id, clients.clone()));
+    }
+}
+
+async fn send_frames(
+    mut stream: TcpStream,
+    mut frames: mpsc::Receiver<Arc<Vec<u8>>>,
+    client_id: ClientId,
+    clients: Arc<Clients>,
+) {
+    while let Some(packet) = frames.recv().await {
+        if let Err(error) = stream.write_all(&packet).await {
+            debug!("Unable to send frame to client {}: {}", client_id, error);
+            clients.remove(client_id, LeaveReason::Disconnected);
+            return;
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Certificates for local testing of the encrypted transports
+
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+/// Generates a self-signed certificate for the given names, meant for local testing
+pub fn self_signed_certificate(
+    names: &[&str],
+) -> Result<(CertificateDer<'static>, PrivateKeyDer<'static>), rcgen::Error> {
+    let names = names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let certified_key = rcgen::generate_simple_self_signed(names)?;
+
+    let certificate = certified_key.cert.der().clone();
+    let key = PrivatePkcs8KeyDer::from(certified_key.key_pair.serialize_der()).into();
+
+    Ok((certificate, key))
+}
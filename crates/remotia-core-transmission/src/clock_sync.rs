@@ -0,0 +1,390 @@
+//! NTP-like synchronization of the clock of a client with the one of a server, so that
+//! timestamps produced on the server (e.g. at capture) can be compared with the ones of the
+//! client (e.g. at render).
+//!
+//! Each exchange yields an offset sample, whose error is bounded by half the round trip time:
+//! the sample with the lowest round trip within a window is taken as the offset, while the skew
+//! is estimated by linear regression of the offsets over time.
+
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use log::{debug, info};
+use remotia_core::common::{
+    helpers::time::{now_timestamp_micros, Clock},
+    network::clock::ClockSyncPacket,
+};
+use tokio::{
+    net::{ToSocketAddrs, UdpSocket},
+    task::JoinHandle,
+};
+
+use crate::connection::ErrorBackoff;
+
+/// Number of filtered offsets the skew is estimated on
+const SKEW_HISTORY: usize = 64;
+
+/// Minimum time span of the filtered offsets for the skew to be estimated
+const MIN_SKEW_SPAN: f64 = 1_000_000.0;
+
+fn encode_packet(packet: &ClockSyncPacket) -> Vec<u8> {
+    bincode::serde::encode_to_vec(packet, bincode::config::standard()).unwrap()
+}
+
+fn decode_packet(packet: &[u8]) -> Option<ClockSyncPacket> {
+    bincode::serde::decode_from_slice(packet, bincode::config::standard())
+        .ok()
+        .map(|(packet, _)| packet)
+}
+
+/// Outcome of an exchange, in microseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSample {
+    /// Local time at which the response has been received
+    pub local_time: u128,
+
+    /// Remote time minus local time
+    pub offset: f64,
+    pub round_trip: f64,
+}
+
+impl ClockSample {
+    /// Computes the sample of an exchange from the response and its local arrival time
+    pub fn new(response: &ClockSyncPacket, arrival_timestamp: u64) -> Self {
+        let origin = response.origin_timestamp as f64;
+        let receive = response.receive_timestamp as f64;
+        let transmit = response.transmit_timestamp as f64;
+        let arrival = arrival_timestamp as f64;
+
+        Self {
+            local_time: arrival_timestamp as u128,
+            offset: ((receive - origin) + (transmit - arrival)) / 2.0,
+            round_trip: ((arrival - origin) - (transmit - receive)).max(0.0),
+        }
+    }
+}
+
+/// Relation between the local clock and the remote one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockEstimate {
+    /// Remote time minus local time at the reference time, in microseconds
+    pub offset: f64,
+
+    /// Drift of the remote clock with respect to the local one, in microseconds per microsecond
+    pub skew: f64,
+
+    /// Local time the offset has been measured at, in microseconds
+    pub reference: u128,
+
+    /// Round trip time of the exchange the offset has been measured with, in microseconds
+    pub round_trip: f64,
+}
+
+impl ClockEstimate {
+    /// Converts a local time to the remote clock, both in microseconds
+    pub fn remote_time(&self, local_time: u128) -> u128 {
+        let elapsed = local_time as f64 - self.reference as f64;
+        (local_time as f64 + self.offset + self.skew * elapsed).max(0.0) as u128
+    }
+}
+
+/// Estimates the offset and skew of a remote clock from the samples of the exchanges
+#[derive(Debug, Clone)]
+pub struct OffsetEstimator {
+    window: usize,
+    samples: VecDeque<ClockSample>,
+    filtered: VecDeque<ClockSample>,
+}
+
+impl OffsetEstimator {
+    /// Filters the offsets over the last `window` samples
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "Window must not be empty");
+        Self {
+            window,
+            samples: VecDeque::new(),
+            filtered: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, sample: ClockSample) {
+        self.samples.push_back(sample);
+        if self.samples.len() > self.window {
+            self.samples.pop_front();
+        }
+
+        let best = *self
+            .samples
+            .iter()
+            .min_by(|a, b| a.round_trip.total_cmp(&b.round_trip))
+            .unwrap();
+
+        if self.filtered.back() != Some(&best) {
+            self.filtered.push_back(best);
+            if self.filtered.len() > SKEW_HISTORY {
+                self.filtered.pop_front();
+            }
+        }
+    }
+
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        let best = self.filtered.back()?;
+
+        Some(ClockEstimate {
+            offset: best.offset,
+            skew: self.skew(),
+            reference: best.local_time,
+            round_trip: best.round_trip,
+        })
+    }
+
+    fn skew(&self) -> f64 {
+        let (first, last) = match (self.filtered.front(), self.filtered.back()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return 0.0,
+        };
+        if ((last.local_time - first.local_time) as f64) < MIN_SKEW_SPAN {
+            return 0.0;
+        }
+
+        let origin = first.local_time as f64;
+        let count = self.filtered.len() as f64;
+        let mean_x = self
+            .filtered
+            .iter()
+            .map(|sample| sample.local_time as f64 - origin)
+            .sum::<f64>()
+            / count;
+        let mean_y = self
+            .filtered
+            .iter()
+            .map(|sample| sample.offset)
+            .sum::<f64>()
+            / count;
+
+        let (numerator, denominator) =
+            self.filtered
+                .iter()
+                .fold((0.0, 0.0), |(numerator, denominator), sample| {
+                    let x = sample.local_time as f64 - origin - mean_x;
+                    (
+                        numerator + x * (sample.offset - mean_y),
+                        denominator + x * x,
+                    )
+                });
+
+        if denominator == 0.0 {
+            0.0
+        } else {
+            numerator / denominator
+        }
+    }
+}
+
+/// Clock of the server, as estimated by a `ClockSynchronizer`. Until the first exchange
+/// completes, the local clock is used.
+#[derive(Debug, Clone, Default)]
+pub struct SyncedClock {
+    estimate: Arc<RwLock<Option<ClockEstimate>>>,
+}
+
+impl SyncedClock {
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        *self.estimate.read().unwrap()
+    }
+
+    pub fn is_synchronized(&self) -> bool {
+        self.estimate().is_some()
+    }
+
+    /// Current time of the server, in microseconds since the Unix epoch
+    pub fn now_micros(&self) -> u128 {
+        let local_time = now_timestamp_micros();
+        match self.estimate() {
+            Some(estimate) => estimate.remote_time(local_time),
+            None => local_time,
+        }
+    }
+}
+
+impl Clock for SyncedClock {
+    fn now(&self) -> u128 {
+        self.now_micros() / 1000
+    }
+}
+
+/// Answers the requests of the `ClockSynchronizer`s of the clients
+pub struct ClockSyncServer {
+    socket: UdpSocket,
+}
+
+impl ClockSyncServer {
+    pub async fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address).await?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    pub async fn run(self) {
+        let mut packet_buffer = [0; 64];
+        let mut errors = ErrorBackoff::default();
+
+        loop {
+            let (packet_size, client) = match self.socket.recv_from(&mut packet_buffer).await {
+                Ok(received) => {
+                    errors.reset();
+                    received
+                }
+                Err(error) => {
+                    debug!("Unable to receive clock synchronization request: {}", error);
+                    errors.wait().await;
+                    continue;
+                }
+            };
+            let receive_timestamp = now_timestamp_micros() as u64;
+
+            let request = match decode_packet(&packet_buffer[..packet_size]) {
+                Some(request) => request,
+                None => {
+                    debug!("Invalid clock synchronization request from {}", client);
+                    continue;
+                }
+            };
+
+            let response = ClockSyncPacket {
+                origin_timestamp: request.origin_timestamp,
+                receive_timestamp,
+                transmit_timestamp: now_timestamp_micros() as u64,
+            };
+
+            if let Err(error) = self.socket.send_to(&encode_packet(&response), client).await {
+                debug!("Unable to respond to {}: {}", client, error);
+            }
+        }
+    }
+}
+
+/// Periodically exchanges timestamps with a `ClockSyncServer`, keeping a `SyncedClock` up to
+/// date
+pub struct ClockSynchronizer {
+    socket: UdpSocket,
+    clock: SyncedClock,
+
+    interval: Duration,
+    estimator: OffsetEstimator,
+}
+
+impl ClockSynchronizer {
+    pub async fn connect(server: impl ToSocketAddrs) -> io::Result<Self> {
+        let server = tokio::net::lookup_host(server)
+            .await?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No server address"))?;
+        let local_address: SocketAddr = if server.is_ipv4() {
+            "0.0.0.0:0".parse().unwrap()
+        } else {
+            "[::]:0".parse().unwrap()
+        };
+
+        let socket = UdpSocket::bind(local_address).await?;
+        socket.connect(server).await?;
+
+        Ok(Self {
+            socket,
+            clock: SyncedClock::default(),
+            interval: Duration::from_secs(1),
+            estimator: OffsetEstimator::new(8),
+        })
+    }
+
+    /// Sets the time between two exchanges (1s by default)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the number of exchanges the offset is filtered on (8 by default)
+    pub fn window(mut self, window: usize) -> Self {
+        self.estimator = OffsetEstimator::new(window);
+        self
+    }
+
+    pub fn clock(&self) -> SyncedClock {
+        self.clock.clone()
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    pub async fn run(mut self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        let mut packet_buffer = [0; 64];
+
+        loop {
+            ticker.tick().await;
+
+            let origin_timestamp = now_timestamp_micros() as u64;
+            let request = ClockSyncPacket {
+                origin_timestamp,
+                receive_timestamp: 0,
+                transmit_timestamp: 0,
+            };
+            if let Err(error) = self.socket.send(&encode_packet(&request)).await {
+                debug!("Unable to send clock synchronization request: {}", error);
+                continue;
+            }
+
+            // Responses to previous requests are discarded, only the current one is waited for
+            let response = tokio::time::timeout(self.interval, async {
+                loop {
+                    let packet_size = self.socket.recv(&mut packet_buffer).await?;
+                    match decode_packet(&packet_buffer[..packet_size]) {
+                        Some(response) if response.origin_timestamp == origin_timestamp => {
+                            return io::Result::Ok(response)
+                        }
+                        _ => debug!("Discarding stale clock synchronization response"),
+                    }
+                }
+            })
+            .await;
+
+            let response = match response {
+                Ok(Ok(response)) => response,
+                Ok(Err(error)) => {
+                    debug!(
+                        "Unable to receive clock synchronization response: {}",
+                        error
+                    );
+                    continue;
+                }
+                Err(_) => {
+                    debug!("Clock synchronization request timed out");
+                    continue;
+                }
+            };
+
+            let sample = ClockSample::new(&response, now_timestamp_micros() as u64);
+            self.estimator.push(sample);
+
+            let estimate = self.estimator.estimate();
+            if !self.clock.is_synchronized() {
+                info!("Clock synchronized: {:?}", estimate);
+            }
+            *self.clock.estimate.write().unwrap() = estimate;
+        }
+    }
+}
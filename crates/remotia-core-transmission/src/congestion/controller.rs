@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::traits::{BorrowFrameProperties, FrameProcessor, FrameProperties};
+use tokio::{
+    net::{ToSocketAddrs, UdpSocket},
+    sync::watch,
+    task::JoinHandle,
+};
+
+use crate::{connection::ErrorBackoff, remvsp::MAX_DATAGRAM_SIZE};
+
+use super::{
+    decode_feedback,
+    estimator::{BandwidthEstimator, FrameResult},
+};
+
+/// Time between two checks of the frames waiting for feedback, when none is received
+const TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+struct SentFrame {
+    frame_id: u64,
+    sent_at: Instant,
+    size: usize,
+
+    /// Arrival time on the receiver clock, once reported
+    arrival_time: Option<Duration>,
+}
+
+/// Estimates the available bandwidth from the feedback of a `FeedbackReporter`, publishing the
+/// target bitrate the encoders should follow.
+///
+/// The controller is meant to be placed right after the frame sender, so that the send time of
+/// each frame is recorded along with its id and the size of its buffer. Frames missing from a
+/// report listing both older and newer ones are considered lost, as are the frames not reported
+/// within the feedback timeout.
+pub struct BitrateController<K, P> {
+    buffer_key: K,
+    frame_id_key: P,
+    bitrate_key: Option<P>,
+
+    socket: Option<UdpSocket>,
+    feedback_timeout: Duration,
+    local_address: SocketAddr,
+    estimator: Option<BandwidthEstimator>,
+
+    sent_frames: Arc<Mutex<VecDeque<SentFrame>>>,
+    target_bitrate: Arc<watch::Sender<u64>>,
+    feedback_handler: Option<JoinHandle<()>>,
+}
+
+impl<K, P> BitrateController<K, P> {
+    /// Binds the socket the feedback is received on
+    pub async fn bind(
+        buffer_key: K,
+        frame_id_key: P,
+        address: impl ToSocketAddrs,
+    ) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address).await?;
+        let local_address = socket.local_addr()?;
+        let estimator = BandwidthEstimator::default();
+
+        Ok(Self {
+            buffer_key,
+            frame_id_key,
+            bitrate_key: None,
+            socket: Some(socket),
+            feedback_timeout: Duration::from_secs(1),
+            local_address,
+            target_bitrate: Arc::new(watch::channel(estimator.target_bitrate()).0),
+            estimator: Some(estimator),
+            sent_frames: Arc::new(Mutex::new(VecDeque::new())),
+            feedback_handler: None,
+        })
+    }
+
+    /// Replaces the default estimator, which starts from 1 Mbps
+    pub fn estimator(mut self, estimator: BandwidthEstimator) -> Self {
+        self.target_bitrate.send_replace(estimator.target_bitrate());
+        self.estimator = Some(estimator);
+        self
+    }
+
+    /// Sets how long a frame may wait to be reported before being considered lost
+    /// (1 second by default)
+    pub fn feedback_timeout(mut self, timeout: Duration) -> Self {
+        self.feedback_timeout = timeout;
+        self
+    }
+
+    /// Sets the key of the property the current target bitrate is written to
+    pub fn bitrate_key(mut self, key: P) -> Self {
+        self.bitrate_key = Some(key);
+        self
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    /// Target bitrate in bits per second, updated on each feedback
+    pub fn target_bitrate(&self) -> watch::Receiver<u64> {
+        self.target_bitrate.subscribe()
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for BitrateController<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowFrameProperties<K, BytesMut> + FrameProperties<P, u128> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        if let (Some(socket), Some(estimator)) = (self.socket.take(), self.estimator.take()) {
+            self.feedback_handler = Some(spawn_feedback_handler(
+                socket,
+                estimator,
+                self.feedback_timeout,
+                self.sent_frames.clone(),
+                self.target_bitrate.clone(),
+            ));
+        }
+
+        if let Some(frame_id) = frame_data.get(&self.frame_id_key) {
+            let now = Instant::now();
+            let size = frame_data.get_ref(&self.buffer_key).unwrap().len();
+
+            self.sent_frames.lock().unwrap().push_back(SentFrame {
+                frame_id: frame_id as u64,
+                sent_at: now,
+                size,
+                arrival_time: None,
+            });
+        }
+
+        if let Some(bitrate_key) = self.bitrate_key {
+            frame_data.set(bitrate_key, *self.target_bitrate.borrow() as u128);
+        }
+
+        Some(frame_data)
+    }
+}
+
+impl<K, P> Drop for BitrateController<K, P> {
+    fn drop(&mut self) {
+        if let Some(feedback_handler) = &self.feedback_handler {
+            feedback_handler.abort();
+        }
+    }
+}
+
+/// Records the reported arrivals, returning the results of the oldest frames, as long as each
+/// one has either been reported or can be considered lost
+fn resolve_frames(
+    sent_frames: &mut VecDeque<SentFrame>,
+    arrivals: &HashMap<u64, Duration>,
+    feedback_timeout: Duration,
+    now: Instant,
+) -> Vec<FrameResult> {
+    for frame in sent_frames.iter_mut() {
+        if let Some(arrival_time) = arrivals.get(&frame.frame_id) {
+            frame.arrival_time = Some(*arrival_time);
+        }
+    }
+
+    // Reports list all the arrivals within their range of ids
+    let reported_range = arrivals.keys().min().zip(arrivals.keys().max());
+    let is_lost = |frame: &SentFrame| {
+        reported_range
+            .is_some_and(|(first, last)| *first < frame.frame_id && frame.frame_id < *last)
+            || now.duration_since(frame.sent_at) >= feedback_timeout
+    };
+
+    let resolved_count = sent_frames
+        .iter()
+        .take_while(|frame| frame.arrival_time.is_some() || is_lost(frame))
+        .count();
+
+    sent_frames
+        .drain(..resolved_count)
+        .map(|frame| FrameResult {
+            sent_at: frame.sent_at,
+            size: frame.size,
+            arrival_time: frame.arrival_time,
+        })
+        .collect()
+}
+
+fn spawn_feedback_handler(
+    socket: UdpSocket,
+    mut estimator: BandwidthEstimator,
+    feedback_timeout: Duration,
+    sent_frames: Arc<Mutex<VecDeque<SentFrame>>>,
+    target_bitrate: Arc<watch::Sender<u64>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut packet_buffer = vec![0; MAX_DATAGRAM_SIZE];
+        let mut errors = ErrorBackoff::default();
+
+        loop {
+            let received =
+                tokio::time::timeout(TIMEOUT_CHECK_INTERVAL, socket.recv(&mut packet_buffer)).await;
+
+            let feedback = match received {
+                Ok(Ok(packet_size)) => {
+                    errors.reset();
+                    match decode_feedback(&packet_buffer[..packet_size]) {
+                        Some(feedback) => Some(feedback),
+                        None => {
+                            debug!("Invalid feedback of {} bytes", packet_size);
+                            continue;
+                        }
+                    }
+                }
+                Ok(Err(error)) => {
+                    debug!("Unable to receive feedback: {}", error);
+                    errors.wait().await;
+                    None
+                }
+                Err(_) => None,
+            };
+
+            let arrivals: HashMap<u64, Duration> = feedback
+                .iter()
+                .flat_map(|feedback| &feedback.arrivals)
+                .map(|arrival| {
+                    (
+                        arrival.frame_id,
+                        Duration::from_micros(arrival.arrival_time),
+                    )
+                })
+                .collect();
+
+            let now = Instant::now();
+            let results = resolve_frames(
+                &mut sent_frames.lock().unwrap(),
+                &arrivals,
+                feedback_timeout,
+                now,
+            );
+            if results.is_empty() {
+                continue;
+            }
+
+            let bitrate = estimator.on_feedback(&results, now);
+            debug!(
+                "Target bitrate: {} bps ({:?}, {} frame results)",
+                bitrate,
+                estimator.usage(),
+                results.len()
+            );
+            target_bitrate.send_replace(bitrate);
+        }
+    })
+}
@@ -0,0 +1,306 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Number of delay samples the trend is computed on
+const TRENDLINE_WINDOW: usize = 20;
+const TRENDLINE_SMOOTHING: f64 = 0.9;
+const TRENDLINE_GAIN: f64 = 4.0;
+const MAX_TREND_DELTAS: usize = 60;
+
+const INITIAL_THRESHOLD: f64 = 12.5;
+const THRESHOLD_RANGE: (f64, f64) = (6.0, 600.0);
+const THRESHOLD_INCREASE_RATE: f64 = 0.0087;
+const THRESHOLD_DECREASE_RATE: f64 = 0.039;
+
+/// Time span over which the received bitrate is measured
+const ACKED_WINDOW: Duration = Duration::from_millis(500);
+
+const DECREASE_FACTOR: f64 = 0.85;
+const INCREASE_FACTOR_PER_SECOND: f64 = 1.08;
+const HIGH_LOSS: f64 = 0.1;
+
+/// Outcome of a frame, as learned from the feedback of the receiver
+#[derive(Debug, Clone, Copy)]
+pub struct FrameResult {
+    pub sent_at: Instant,
+    pub size: usize,
+
+    /// Arrival time on the receiver clock, none if the frame has been lost
+    pub arrival_time: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandwidthUsage {
+    Normal,
+    Underusing,
+    Overusing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RateControlState {
+    Hold,
+    Increase,
+}
+
+/// Slope of the queuing delay over time, estimated by linear regression
+#[derive(Debug, Clone, Default)]
+struct TrendlineFilter {
+    accumulated_delay: f64,
+    smoothed_delay: f64,
+    samples: VecDeque<(f64, f64)>,
+    deltas_count: usize,
+}
+
+impl TrendlineFilter {
+    fn update(&mut self, arrival_time: f64, delay_delta: f64) {
+        self.deltas_count = (self.deltas_count + 1).min(MAX_TREND_DELTAS);
+        self.accumulated_delay += delay_delta;
+        self.smoothed_delay = TRENDLINE_SMOOTHING * self.smoothed_delay
+            + (1.0 - TRENDLINE_SMOOTHING) * self.accumulated_delay;
+
+        self.samples.push_back((arrival_time, self.smoothed_delay));
+        if self.samples.len() > TRENDLINE_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    fn trend(&self) -> f64 {
+        if self.samples.len() < TRENDLINE_WINDOW {
+            return 0.0;
+        }
+
+        let count = self.samples.len() as f64;
+        let mean_x = self.samples.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = self.samples.iter().map(|(_, y)| y).sum::<f64>() / count;
+
+        let (numerator, denominator) =
+            self.samples
+                .iter()
+                .fold((0.0, 0.0), |(numerator, denominator), (x, y)| {
+                    (
+                        numerator + (x - mean_x) * (y - mean_y),
+                        denominator + (x - mean_x).powi(2),
+                    )
+                });
+
+        if denominator == 0.0 {
+            return 0.0;
+        }
+
+        numerator / denominator * self.deltas_count as f64 * TRENDLINE_GAIN
+    }
+}
+
+/// Compares the delay trend with a threshold which adapts to the trend itself, so that the
+/// estimation does not starve when competing with loss-based flows
+#[derive(Debug, Clone)]
+struct OveruseDetector {
+    threshold: f64,
+    previous_trend: f64,
+    last_update: Option<Instant>,
+}
+
+impl OveruseDetector {
+    fn new() -> Self {
+        Self {
+            threshold: INITIAL_THRESHOLD,
+            previous_trend: 0.0,
+            last_update: None,
+        }
+    }
+
+    fn detect(&mut self, trend: f64, now: Instant) -> BandwidthUsage {
+        let usage = if trend > self.threshold && trend >= self.previous_trend {
+            BandwidthUsage::Overusing
+        } else if trend < -self.threshold {
+            BandwidthUsage::Underusing
+        } else {
+            BandwidthUsage::Normal
+        };
+        self.previous_trend = trend;
+
+        let elapsed = self
+            .last_update
+            .map(|last_update| {
+                now.duration_since(last_update)
+                    .min(Duration::from_millis(100))
+            })
+            .unwrap_or_default();
+        self.last_update = Some(now);
+
+        let distance = trend.abs() - self.threshold;
+        if distance <= 15.0 {
+            let rate = if trend.abs() < self.threshold {
+                THRESHOLD_DECREASE_RATE
+            } else {
+                THRESHOLD_INCREASE_RATE
+            };
+            self.threshold = (self.threshold + rate * distance * elapsed.as_millis() as f64)
+                .clamp(THRESHOLD_RANGE.0, THRESHOLD_RANGE.1);
+        }
+
+        usage
+    }
+}
+
+/// Delay-based estimation of the available bandwidth, after Google Congestion Control.
+///
+/// The growth of the one-way delay between consecutive frames reveals the queues building up
+/// along the path: the target bitrate is lowered below the received bitrate on overuse and
+/// increased multiplicatively otherwise. High loss rates also lower the target bitrate. Only
+/// differences of arrival times are used, hence the clocks of the peers need not be in sync.
+#[derive(Debug, Clone)]
+pub struct BandwidthEstimator {
+    target_bitrate: f64,
+    min_bitrate: u64,
+    max_bitrate: u64,
+
+    trendline: TrendlineFilter,
+    detector: OveruseDetector,
+    state: RateControlState,
+    usage: BandwidthUsage,
+
+    last_received: Option<(Instant, Duration)>,
+    received: VecDeque<(Duration, usize)>,
+    last_update: Option<Instant>,
+}
+
+impl Default for BandwidthEstimator {
+    fn default() -> Self {
+        Self::new(1_000_000)
+    }
+}
+
+impl BandwidthEstimator {
+    pub fn new(initial_bitrate: u64) -> Self {
+        Self {
+            target_bitrate: initial_bitrate as f64,
+            min_bitrate: 50_000,
+            max_bitrate: 100_000_000,
+            trendline: TrendlineFilter::default(),
+            detector: OveruseDetector::new(),
+            state: RateControlState::Increase,
+            usage: BandwidthUsage::Normal,
+            last_received: None,
+            received: VecDeque::new(),
+            last_update: None,
+        }
+    }
+
+    /// Sets the range the target bitrate is kept within (50 Kbps to 100 Mbps by default)
+    pub fn bitrate_range(mut self, min_bitrate: u64, max_bitrate: u64) -> Self {
+        assert!(min_bitrate <= max_bitrate, "Invalid bitrate range");
+        self.min_bitrate = min_bitrate;
+        self.max_bitrate = max_bitrate;
+        self.target_bitrate = self
+            .target_bitrate
+            .clamp(min_bitrate as f64, max_bitrate as f64);
+        self
+    }
+
+    /// Target bitrate in bits per second
+    pub fn target_bitrate(&self) -> u64 {
+        self.target_bitrate as u64
+    }
+
+    pub fn usage(&self) -> BandwidthUsage {
+        self.usage
+    }
+
+    /// Bitrate at which the frames have been received lately, in bits per second
+    pub fn received_bitrate(&self) -> Option<u64> {
+        let (first, _) = self.received.front()?;
+        let (last, _) = self.received.back()?;
+        let span = last.saturating_sub(*first);
+        if span.is_zero() {
+            return None;
+        }
+
+        let bytes: usize = self.received.iter().skip(1).map(|(_, size)| size).sum();
+        Some((bytes as f64 * 8.0 / span.as_secs_f64()) as u64)
+    }
+
+    /// Updates the estimation with the results of the frames in send order, returning the new
+    /// target bitrate
+    pub fn on_feedback(&mut self, results: &[FrameResult], now: Instant) -> u64 {
+        if results.is_empty() {
+            return self.target_bitrate();
+        }
+
+        for result in results {
+            let arrival_time = match result.arrival_time {
+                Some(arrival_time) => arrival_time,
+                None => continue,
+            };
+
+            if let Some((last_sent_at, last_arrival_time)) = self.last_received {
+                if result.sent_at >= last_sent_at && arrival_time >= last_arrival_time {
+                    let send_delta = result.sent_at.duration_since(last_sent_at);
+                    let arrival_delta = arrival_time - last_arrival_time;
+                    let delay_delta =
+                        (arrival_delta.as_secs_f64() - send_delta.as_secs_f64()) * 1000.0;
+                    self.trendline
+                        .update(arrival_time.as_secs_f64() * 1000.0, delay_delta);
+                }
+            }
+            self.last_received = Some((result.sent_at, arrival_time));
+
+            self.received.push_back((arrival_time, result.size));
+            while self
+                .received
+                .front()
+                .is_some_and(|(first, _)| arrival_time.saturating_sub(*first) > ACKED_WINDOW)
+            {
+                self.received.pop_front();
+            }
+        }
+
+        self.usage = self.detector.detect(self.trendline.trend(), now);
+
+        let elapsed = self
+            .last_update
+            .map(|last_update| now.duration_since(last_update).min(Duration::from_secs(1)))
+            .unwrap_or_default();
+        self.last_update = Some(now);
+
+        let received_bitrate = self.received_bitrate().map(|bitrate| bitrate as f64);
+        match self.usage {
+            BandwidthUsage::Overusing => {
+                // Lowering below the received bitrate drains the queues, a persisting overuse
+                // only means they have not been drained yet
+                let decreased = received_bitrate.unwrap_or(self.target_bitrate) * DECREASE_FACTOR;
+                self.target_bitrate = self.target_bitrate.min(decreased);
+                self.state = RateControlState::Hold;
+            }
+            BandwidthUsage::Underusing => self.state = RateControlState::Hold,
+            BandwidthUsage::Normal => match self.state {
+                RateControlState::Hold => self.state = RateControlState::Increase,
+                RateControlState::Increase => {
+                    let mut increased = self.target_bitrate
+                        * INCREASE_FACTOR_PER_SECOND.powf(elapsed.as_secs_f64());
+                    if let Some(received_bitrate) = received_bitrate {
+                        increased = increased.min(1.5 * received_bitrate + 10_000.0);
+                    }
+                    self.target_bitrate = self.target_bitrate.max(increased);
+                }
+            },
+        }
+
+        let lost = results
+            .iter()
+            .filter(|result| result.arrival_time.is_none())
+            .count();
+        let loss = lost as f64 / results.len() as f64;
+        if loss > HIGH_LOSS {
+            self.target_bitrate *= 1.0 - 0.5 * loss;
+        }
+
+        self.target_bitrate = self
+            .target_bitrate
+            .clamp(self.min_bitrate as f64, self.max_bitrate as f64);
+
+        self.target_bitrate()
+    }
+}
@@ -0,0 +1,18 @@
+//! Congestion control: the receiver reports the arrival time of each frame, from which the sender
+//! estimates the available bandwidth and publishes a target bitrate for the encoders to follow.
+
+use remotia_core::common::network::congestion::TransportFeedback;
+
+pub mod controller;
+pub mod estimator;
+pub mod reporter;
+
+pub(crate) fn encode_feedback(feedback: &TransportFeedback) -> Vec<u8> {
+    bincode::serde::encode_to_vec(feedback, bincode::config::standard()).unwrap()
+}
+
+pub(crate) fn decode_feedback(packet: &[u8]) -> Option<TransportFeedback> {
+    bincode::serde::decode_from_slice(packet, bincode::config::standard())
+        .ok()
+        .map(|(feedback, _)| feedback)
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use remotia_core::{
+    common::network::congestion::{FrameArrival, TransportFeedback},
+    traits::{FrameProcessor, FrameProperties},
+};
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+use super::encode_feedback;
+
+/// Records the arrival time of the received frames, periodically reporting them to the
+/// `BitrateController` of the sender. Each report repeats the latest arrivals already reported,
+/// so that the loss of a report does not make its frames look lost. Frames without an id are
+/// not reported.
+pub struct FeedbackReporter<P> {
+    frame_id_key: P,
+
+    socket: UdpSocket,
+    interval: Duration,
+    repeated_arrivals: usize,
+
+    started_at: Instant,
+    last_report: Instant,
+    arrivals: VecDeque<FrameArrival>,
+    new_arrivals: usize,
+}
+
+impl<P> FeedbackReporter<P> {
+    /// Binds a socket reporting to the controller listening at the given address
+    pub async fn connect(frame_id_key: P, controller: impl ToSocketAddrs) -> io::Result<Self> {
+        let controller = tokio::net::lookup_host(controller)
+            .await?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No address to report"))?;
+        let local_address: SocketAddr = if controller.is_ipv4() {
+            "0.0.0.0:0".parse().unwrap()
+        } else {
+            "[::]:0".parse().unwrap()
+        };
+
+        let socket = UdpSocket::bind(local_address).await?;
+        socket.connect(controller).await?;
+
+        let now = Instant::now();
+        Ok(Self {
+            frame_id_key,
+            socket,
+            interval: Duration::from_millis(50),
+            repeated_arrivals: 32,
+            started_at: now,
+            last_report: now,
+            arrivals: VecDeque::new(),
+            new_arrivals: 0,
+        })
+    }
+
+    /// Sets the time between two reports (50ms by default)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how many of the arrivals already reported are repeated in each report (32 by default)
+    pub fn repeated_arrivals(mut self, count: usize) -> Self {
+        self.repeated_arrivals = count;
+        self
+    }
+}
+
+#[async_trait]
+impl<F, P> FrameProcessor<F> for FeedbackReporter<P>
+where
+    P: Copy + Send,
+    F: FrameProperties<P, u128> + Send + 'static,
+{
+    async fn process(&mut self, frame_data: F) -> Option<F> {
+        let now = Instant::now();
+
+        if let Some(frame_id) = frame_data.get(&self.frame_id_key) {
+            self.arrivals.push_back(FrameArrival {
+                frame_id: frame_id as u64,
+                arrival_time: now.duration_since(self.started_at).as_micros() as u64,
+            });
+            self.new_arrivals += 1;
+        }
+
+        if self.new_arrivals > 0 && now.duration_since(self.last_report) >= self.interval {
+            let feedback = TransportFeedback {
+                arrivals: self.arrivals.iter().copied().collect(),
+            };
+            debug!(
+                "Reporting {} frame arrivals ({} new)",
+                feedback.arrivals.len(),
+                self.new_arrivals
+            );
+
+            if let Err(error) = self.socket.send(&encode_feedback(&feedback)).await {
+                warn!("Unable to send feedback: {}", error);
+            }
+            self.last_report = now;
+            self.new_arrivals = 0;
+
+            let repeated_arrivals = self.arrivals.len().min(self.repeated_arrivals);
+            self.arrivals
+                .drain(..self.arrivals.len() - repeated_arrivals);
+        }
+
+        Some(frame_data)
+    }
+}
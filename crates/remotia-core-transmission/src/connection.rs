@@ -0,0 +1,302 @@
+//! Establishment and re-establishment of the connections used by `TcpFrameSender` and
+//! `TcpFrameReceiver`, either connecting to a server or accepting clients.
+
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use log::{debug, info};
+use tokio::{
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{mpsc, watch, Notify},
+    task::JoinHandle,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+}
+
+/// Exponential delay between failed connection attempts
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    multiplier: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(100), Duration::from_secs(5))
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            multiplier: 2,
+        }
+    }
+
+    /// Sets the factor the delay grows by after each failed attempt (2 by default)
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    fn next(&self, delay: Duration) -> Duration {
+        (delay * self.multiplier).min(self.max)
+    }
+}
+
+/// Delay of the loops serving a socket after a failed receive, so that a persistent error does
+/// not make them spin. It grows with each consecutive failure and is reset by a success.
+#[derive(Default)]
+pub(crate) struct ErrorBackoff {
+    backoff: Backoff,
+    delay: Option<Duration>,
+}
+
+impl ErrorBackoff {
+    pub fn reset(&mut self) {
+        self.delay = None;
+    }
+
+    pub async fn wait(&mut self) {
+        let delay = match self.delay {
+            Some(delay) => self.backoff.next(delay),
+            None => self.backoff.initial,
+        };
+
+        self.delay = Some(delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Source of the connections managed by a `ConnectionManager`
+#[async_trait]
+pub trait Connector: Send + 'static {
+    type Stream: Send + 'static;
+
+    async fn connect(&mut self) -> io::Result<Self::Stream>;
+}
+
+/// Connects to a listening server
+pub struct TcpClient<A> {
+    address: A,
+}
+
+impl<A> TcpClient<A> {
+    pub fn new(address: A) -> Self {
+        Self { address }
+    }
+}
+
+#[async_trait]
+impl<A> Connector for TcpClient<A>
+where
+    A: ToSocketAddrs + Clone + Send + Sync + 'static,
+{
+    type Stream = TcpStream;
+
+    async fn connect(&mut self) -> io::Result<TcpStream> {
+        let stream = TcpStream::connect(self.address.clone()).await?;
+        info!("Connected to {}", stream.peer_addr()?);
+        Ok(stream)
+    }
+}
+
+/// Accepts one client at a time, waiting for the next one whenever the connection is lost
+pub struct TcpServer {
+    listener: TcpListener,
+}
+
+impl TcpServer {
+    pub async fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+#[async_trait]
+impl Connector for TcpServer {
+    type Stream = TcpStream;
+
+    async fn connect(&mut self) -> io::Result<TcpStream> {
+        let (stream, address) = self.listener.accept().await?;
+        info!("Accepted connection from {}", address);
+        Ok(stream)
+    }
+}
+
+/// Keeps a connection up, establishing it again in background with backoff whenever it is
+/// reported as lost. Connection state changes can be observed through `state`.
+pub struct ConnectionManager<S = TcpStream> {
+    connector: Option<Box<dyn Connector<Stream = S>>>,
+    backoff: Backoff,
+    connection_timeout: Duration,
+
+    stream: Option<S>,
+    streams: Option<mpsc::Receiver<S>>,
+    connected_once: bool,
+    reconnect: Arc<Notify>,
+    state: watch::Sender<ConnectionState>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl<S: Send + 'static> ConnectionManager<S> {
+    pub fn new(connector: impl Connector<Stream = S>) -> Self {
+        Self {
+            connector: Some(Box::new(connector)),
+            backoff: Backoff::default(),
+            connection_timeout: Duration::from_secs(1),
+            stream: None,
+            streams: None,
+            connected_once: false,
+            reconnect: Arc::new(Notify::new()),
+            state: watch::channel(ConnectionState::Disconnected).0,
+            task: None,
+        }
+    }
+
+    /// Manages an already established connection, which is not re-established once lost
+    pub fn from_stream(stream: S) -> Self {
+        Self {
+            connector: None,
+            backoff: Backoff::default(),
+            connection_timeout: Duration::ZERO,
+            stream: Some(stream),
+            streams: None,
+            connected_once: true,
+            reconnect: Arc::new(Notify::new()),
+            state: watch::channel(ConnectionState::Connected).0,
+            task: None,
+        }
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets how long to wait for the connection to be established before giving up on a frame
+    /// (1 second by default)
+    pub fn connection_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_timeout = timeout;
+        self
+    }
+
+    pub fn state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Current connection, waiting up to the connection timeout for the first one to be
+    /// established. Once the connection has been lost, frames do not wait for it to be
+    /// re-established, so that a sender does not fall behind while the link is down.
+    pub async fn stream(&mut self) -> Option<&mut S> {
+        let wait = !self.connected_once;
+        self.next_stream(wait).await
+    }
+
+    /// Current connection, waiting up to the connection timeout for it to be established even
+    /// after a loss, for the receivers which have nothing to process in the meantime
+    pub async fn wait_stream(&mut self) -> Option<&mut S> {
+        self.next_stream(true).await
+    }
+
+    async fn next_stream(&mut self, wait: bool) -> Option<&mut S> {
+        if self.stream.is_none() {
+            self.start();
+
+            let streams = self.streams.as_mut()?;
+            let stream = if wait {
+                tokio::time::timeout(self.connection_timeout, streams.recv())
+                    .await
+                    .ok()
+                    .flatten()
+            } else {
+                streams.try_recv().ok()
+            };
+
+            self.stream = Some(stream?);
+            self.connected_once = true;
+        }
+
+        self.stream.as_mut()
+    }
+
+    /// Drops the current connection after a failure, starting to establish a new one
+    pub fn disconnect(&mut self, error: &io::Error) {
+        if self.stream.take().is_none() {
+            return;
+        }
+
+        info!("Connection lost: {}", error);
+        self.state.send_replace(ConnectionState::Disconnected);
+        self.reconnect.notify_one();
+    }
+
+    fn start(&mut self) {
+        let connector = match self.connector.take() {
+            Some(connector) => connector,
+            None => return,
+        };
+
+        let (streams_sender, streams) = mpsc::channel(1);
+        self.streams = Some(streams);
+        self.task = Some(tokio::spawn(connection_loop(
+            connector,
+            self.backoff,
+            streams_sender,
+            self.reconnect.clone(),
+            self.state.clone(),
+        )));
+    }
+}
+
+impl<S> Drop for ConnectionManager<S> {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
+
+async fn connection_loop<S: Send + 'static>(
+    mut connector: Box<dyn Connector<Stream = S>>,
+    backoff: Backoff,
+    streams: mpsc::Sender<S>,
+    reconnect: Arc<Notify>,
+    state: watch::Sender<ConnectionState>,
+) {
+    loop {
+        state.send_replace(ConnectionState::Connecting);
+
+        let mut delay = backoff.initial;
+        let stream = loop {
+            match connector.connect().await {
+                Ok(stream) => break stream,
+                Err(error) => {
+                    debug!("Unable to connect ({}), retrying in {:?}", error, delay);
+                    tokio::time::sleep(delay).await;
+                    delay = backoff.next(delay);
+                }
+            }
+        };
+
+        state.send_replace(ConnectionState::Connected);
+        if streams.send(stream).await.is_err() {
+            return;
+        }
+
+        reconnect.notified().await;
+    }
+}
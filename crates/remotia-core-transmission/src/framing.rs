@@ -0,0 +1,103 @@
+use std::num::TryFromIntError;
+
+use remotia_core::traits::OptionalPropertyKey;
+
+/// Header preceding each frame sent over a stream, in little endian:
+/// the payload length (4 bytes), the flags (4 bytes), the frame id (8 bytes) and the
+/// timestamp (8 bytes)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub length: u32,
+    pub flags: u32,
+    pub frame_id: u64,
+    pub timestamp: u64,
+}
+
+impl FrameHeader {
+    pub const SIZE: usize = 24;
+
+    /// Fails if the payload length does not fit the 4 bytes of the header
+    pub fn new(length: usize) -> Result<Self, TryFromIntError> {
+        Ok(Self {
+            length: u32::try_from(length)?,
+            ..Default::default()
+        })
+    }
+
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut output = [0; Self::SIZE];
+        output[..4].copy_from_slice(&self.length.to_le_bytes());
+        output[4..8].copy_from_slice(&self.flags.to_le_bytes());
+        output[8..16].copy_from_slice(&self.frame_id.to_le_bytes());
+        output[16..24].copy_from_slice(&self.timestamp.to_le_bytes());
+        output
+    }
+
+    pub fn decode(input: &[u8; Self::SIZE]) -> Self {
+        Self {
+            length: u32::from_le_bytes(input[..4].try_into().unwrap()),
+            flags: u32::from_le_bytes(input[4..8].try_into().unwrap()),
+            frame_id: u64::from_le_bytes(input[8..16].try_into().unwrap()),
+            timestamp: u64::from_le_bytes(input[16..24].try_into().unwrap()),
+        }
+    }
+}
+
+/// Keys of the frame properties carried by the header, each either `Option<K>` or `()` when
+/// the frames have no such properties
+pub(crate) struct HeaderKeys<P> {
+    pub frame_id: P,
+    pub timestamp: P,
+    pub flags: P,
+}
+
+impl HeaderKeys<()> {
+    pub fn new() -> Self {
+        Self {
+            frame_id: (),
+            timestamp: (),
+            flags: (),
+        }
+    }
+
+    /// Switches to keys of type `K`, none of which is set yet
+    pub fn typed<K>(self) -> HeaderKeys<Option<K>> {
+        HeaderKeys::default()
+    }
+}
+
+impl<K> Default for HeaderKeys<Option<K>> {
+    fn default() -> Self {
+        Self {
+            frame_id: None,
+            timestamp: None,
+            flags: None,
+        }
+    }
+}
+
+impl<P> HeaderKeys<P> {
+    /// Fills the optional header fields from the frame properties
+    pub fn fill<F>(&self, header: &mut FrameHeader, frame_data: &F)
+    where
+        P: OptionalPropertyKey<F, u128>,
+    {
+        let get = |key: &P| key.get_property(frame_data).unwrap_or(0);
+
+        header.frame_id = get(&self.frame_id) as u64;
+        header.timestamp = get(&self.timestamp) as u64;
+        header.flags = get(&self.flags) as u32;
+    }
+
+    /// Writes the optional header fields to the frame properties
+    pub fn apply<F>(&self, header: &FrameHeader, frame_data: &mut F)
+    where
+        P: OptionalPropertyKey<F, u128>,
+    {
+        self.frame_id
+            .set_property(frame_data, header.frame_id as u128);
+        self.timestamp
+            .set_property(frame_data, header.timestamp as u128);
+        self.flags.set_property(frame_data, header.flags as u128);
+    }
+}
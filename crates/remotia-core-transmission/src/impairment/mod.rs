@@ -0,0 +1,264 @@
+//! Emulation of impaired networks (delay, jitter, bandwidth cap, loss, duplication and reordering)
+//! to evaluate transports on loopback, either between processors or as a local UDP proxy.
+//!
+//! The proxy only relays datagrams: a TCP stream cannot be impaired at the packet level, hence
+//! stream transports are to be evaluated with the `ImpairmentEmulator` processors or a
+//! `LoopbackFrameSender` instead.
+
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub mod processor;
+pub mod proxy;
+pub mod trace;
+
+use trace::ImpairmentTrace;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LossModel {
+    None,
+
+    /// Each packet is lost independently with the given probability
+    Random(f64),
+
+    /// Bursty loss, alternating between a good and a bad state
+    GilbertElliott {
+        /// Probability of moving from the good to the bad state at each packet
+        good_to_bad: f64,
+
+        /// Probability of moving from the bad to the good state at each packet
+        bad_to_good: f64,
+
+        good_loss: f64,
+        bad_loss: f64,
+    },
+}
+
+/// Conditions of the emulated network
+#[derive(Debug, Clone)]
+pub struct ImpairmentProfile {
+    delay: Duration,
+    jitter: Duration,
+    bandwidth: Option<u64>,
+    loss: LossModel,
+    duplication: f64,
+    reordering: f64,
+    seed: Option<u64>,
+    trace: Option<ImpairmentTrace>,
+}
+
+impl Default for ImpairmentProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImpairmentProfile {
+    pub fn new() -> Self {
+        Self {
+            delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+            bandwidth: None,
+            loss: LossModel::None,
+            duplication: 0.0,
+            reordering: 0.0,
+            seed: None,
+            trace: None,
+        }
+    }
+
+    /// Sets the one-way delay applied to each packet
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the maximum deviation of the delay, uniformly distributed in `[-jitter, jitter]`
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Caps the throughput of the link in bits per second, queuing the packets exceeding it
+    pub fn bandwidth(mut self, bits_per_second: u64) -> Self {
+        assert!(bits_per_second > 0, "Bandwidth must be positive");
+        self.bandwidth = Some(bits_per_second);
+        self
+    }
+
+    pub fn loss(mut self, loss: LossModel) -> Self {
+        match loss {
+            LossModel::None => {}
+            LossModel::Random(probability) => assert_probability("Loss", probability),
+            LossModel::GilbertElliott {
+                good_to_bad,
+                bad_to_good,
+                good_loss,
+                bad_loss,
+            } => {
+                assert_probability("Good to bad transition", good_to_bad);
+                assert_probability("Bad to good transition", bad_to_good);
+                assert_probability("Good state loss", good_loss);
+                assert_probability("Bad state loss", bad_loss);
+            }
+        }
+
+        self.loss = loss;
+        self
+    }
+
+    /// Sets the probability of a packet being delivered twice
+    pub fn duplication(mut self, probability: f64) -> Self {
+        assert_probability("Duplication", probability);
+        self.duplication = probability;
+        self
+    }
+
+    /// Sets the probability of a packet skipping the delay, overtaking the ones in flight
+    pub fn reordering(mut self, probability: f64) -> Self {
+        assert_probability("Reordering", probability);
+        self.reordering = probability;
+        self
+    }
+
+    /// Makes the emulation reproducible by seeding the random generator
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Varies delay, jitter, bandwidth and random loss over time, following the trace
+    pub fn trace(mut self, trace: ImpairmentTrace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+}
+
+pub(crate) fn assert_probability(name: &str, probability: f64) {
+    assert!(
+        (0.0..=1.0).contains(&probability),
+        "{} probability must be in [0, 1], got {}",
+        name,
+        probability
+    );
+}
+
+/// Decides the fate of each packet according to an `ImpairmentProfile`
+pub struct NetworkEmulator {
+    profile: ImpairmentProfile,
+    rng: StdRng,
+    start: Instant,
+
+    bad_state: bool,
+    link_free_at: Option<Instant>,
+}
+
+impl NetworkEmulator {
+    pub fn new(profile: ImpairmentProfile) -> Self {
+        let rng = match profile.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        Self {
+            profile,
+            rng,
+            start: Instant::now(),
+            bad_state: false,
+            link_free_at: None,
+        }
+    }
+
+    /// Delivery times of a packet of `size` bytes sent at `now`: none if the packet is lost,
+    /// two if duplicated
+    pub fn schedule(&mut self, size: usize, now: Instant) -> Vec<Instant> {
+        let (delay, jitter, bandwidth, loss) = match &self.profile.trace {
+            Some(trace) => {
+                let step = trace.step_at(now.saturating_duration_since(self.start));
+                (
+                    step.delay,
+                    step.jitter,
+                    step.bandwidth,
+                    LossModel::Random(step.loss),
+                )
+            }
+            None => (
+                self.profile.delay,
+                self.profile.jitter,
+                self.profile.bandwidth,
+                self.profile.loss,
+            ),
+        };
+
+        // Lost packets still take their share of the link
+        let sent_at = match bandwidth {
+            Some(bandwidth) => {
+                let transmission_time =
+                    Duration::from_secs_f64((size * 8) as f64 / bandwidth as f64);
+                let link_free_at = self.link_free_at.map_or(now, |free_at| free_at.max(now));
+                let sent_at = link_free_at + transmission_time;
+                self.link_free_at = Some(sent_at);
+                sent_at
+            }
+            None => now,
+        };
+
+        if self.is_lost(loss) {
+            return Vec::new();
+        }
+
+        let copies = if self.rng.gen_bool(self.profile.duplication) {
+            2
+        } else {
+            1
+        };
+
+        (0..copies)
+            .map(|_| {
+                if self.rng.gen_bool(self.profile.reordering) {
+                    return sent_at;
+                }
+
+                sent_at + self.jittered(delay, jitter)
+            })
+            .collect()
+    }
+
+    fn jittered(&mut self, delay: Duration, jitter: Duration) -> Duration {
+        if jitter.is_zero() {
+            return delay;
+        }
+
+        let deviation = self
+            .rng
+            .gen_range(-jitter.as_secs_f64()..=jitter.as_secs_f64());
+        Duration::from_secs_f64((delay.as_secs_f64() + deviation).max(0.0))
+    }
+
+    fn is_lost(&mut self, loss: LossModel) -> bool {
+        match loss {
+            LossModel::None => false,
+            LossModel::Random(probability) => self.rng.gen_bool(probability),
+            LossModel::GilbertElliott {
+                good_to_bad,
+                bad_to_good,
+                good_loss,
+                bad_loss,
+            } => {
+                let transition = if self.bad_state {
+                    bad_to_good
+                } else {
+                    good_to_bad
+                };
+
+                if self.rng.gen_bool(transition) {
+                    self.bad_state = !self.bad_state;
+                }
+
+                let probability = if self.bad_state { bad_loss } else { good_loss };
+                self.rng.gen_bool(probability)
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowFrameProperties, FrameError, FrameProcessor},
+};
+use tokio::sync::Notify;
+
+use super::{ImpairmentProfile, NetworkEmulator};
+
+struct InFlightFrame<F> {
+    delivery: Instant,
+    sequence: u64,
+    frame_data: F,
+}
+
+impl<F> PartialEq for InFlightFrame<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F> Eq for InFlightFrame<F> {}
+
+impl<F> PartialOrd for InFlightFrame<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F> Ord for InFlightFrame<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.delivery, self.sequence).cmp(&(other.delivery, other.sequence))
+    }
+}
+
+struct Shared<F> {
+    in_flight: Mutex<BinaryHeap<Reverse<InFlightFrame<F>>>>,
+    delivered: Notify,
+}
+
+/// Emulated network between two parts of a pipeline, split into the processors the frames
+/// enter and leave it through. Each frame is treated as a packet of the size of its buffer.
+pub struct ImpairmentEmulator<K> {
+    buffer_key: K,
+    profile: ImpairmentProfile,
+}
+
+impl<K> ImpairmentEmulator<K> {
+    pub fn new(buffer_key: K, profile: ImpairmentProfile) -> Self {
+        Self {
+            buffer_key,
+            profile,
+        }
+    }
+
+    /// Creates the processors the frames enter and leave the emulated network through
+    pub fn split<F>(self) -> (ImpairmentInput<F, K>, ImpairmentOutput<F>) {
+        let shared = Arc::new(Shared {
+            in_flight: Mutex::new(BinaryHeap::new()),
+            delivered: Notify::new(),
+        });
+
+        let input = ImpairmentInput {
+            buffer_key: self.buffer_key,
+            emulator: NetworkEmulator::new(self.profile),
+            next_sequence: 0,
+            shared: shared.clone(),
+        };
+        let output = ImpairmentOutput { shared };
+
+        (input, output)
+    }
+}
+
+/// Schedules the delivery of each frame, consuming it to be released by the
+/// `ImpairmentOutput`. Lost frames are reported as `DropReason::EmulatedLoss` and passed on.
+///
+/// Duplicated frames are delivered along with a clone, hence duplication is meant for frames
+/// whose buffers are not redeemed to a pool.
+pub struct ImpairmentInput<F, K> {
+    buffer_key: K,
+    emulator: NetworkEmulator,
+    next_sequence: u64,
+    shared: Arc<Shared<F>>,
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for ImpairmentInput<F, K>
+where
+    K: Send,
+    F: BorrowFrameProperties<K, BytesMut> + FrameError<DropReason> + Clone + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let size = frame_data.get_ref(&self.buffer_key).unwrap().len();
+        let deliveries = self.emulator.schedule(size, Instant::now());
+
+        if deliveries.is_empty() {
+            debug!("Emulated loss of a frame of {} bytes", size);
+            frame_data.report_error(DropReason::EmulatedLoss);
+            return Some(frame_data);
+        }
+
+        let mut frames: Vec<(Instant, F)> = deliveries[1..]
+            .iter()
+            .map(|delivery| (*delivery, frame_data.clone()))
+            .collect();
+        frames.push((deliveries[0], frame_data));
+
+        let mut in_flight = self.shared.in_flight.lock().unwrap();
+        for (delivery, frame_data) in frames {
+            self.next_sequence += 1;
+            in_flight.push(Reverse(InFlightFrame {
+                delivery,
+                sequence: self.next_sequence,
+                frame_data,
+            }));
+        }
+        drop(in_flight);
+
+        self.shared.delivered.notify_one();
+
+        None
+    }
+}
+
+/// Releases the frames in order of their emulated delivery time, each one once it is reached.
+/// Being the source of the frames, it is meant to be the first processor of a component
+/// without predecessors, the frame it is given being discarded.
+pub struct ImpairmentOutput<F> {
+    shared: Arc<Shared<F>>,
+}
+
+impl<F> ImpairmentOutput<F> {
+    pub fn in_flight_frames(&self) -> usize {
+        self.shared.in_flight.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl<F> FrameProcessor<F> for ImpairmentOutput<F>
+where
+    F: Send + 'static,
+{
+    async fn process(&mut self, _frame_data: F) -> Option<F> {
+        loop {
+            let notified = self.shared.delivered.notified();
+
+            let next_delivery = {
+                let mut in_flight = self.shared.in_flight.lock().unwrap();
+                match in_flight.peek() {
+                    Some(next) if next.0.delivery <= Instant::now() => {
+                        return in_flight.pop().map(|delivered| delivered.0.frame_data);
+                    }
+                    Some(next) => Some(next.0.delivery),
+                    None => None,
+                }
+            };
+
+            match next_delivery {
+                Some(delivery) => {
+                    let _ = tokio::time::timeout_at(delivery.into(), notified).await;
+                }
+                None => notified.await,
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{io, net::SocketAddr, sync::Arc, time::Instant};
+
+use log::{debug, warn};
+use tokio::{
+    net::{ToSocketAddrs, UdpSocket},
+    task::JoinHandle,
+};
+
+use crate::{connection::ErrorBackoff, remvsp::MAX_DATAGRAM_SIZE};
+
+use super::{ImpairmentProfile, NetworkEmulator};
+
+/// Local UDP proxy relaying the datagrams of a client to an upstream address, impairing them on
+/// the way. Datagrams sent back by the upstream reach the last client seen, impaired only if a
+/// downstream profile is set.
+///
+/// Only UDP is relayed: TCP streams cannot be impaired by the proxy.
+pub struct UdpImpairmentProxy {
+    socket: Arc<UdpSocket>,
+    upstream: SocketAddr,
+
+    upstream_emulator: NetworkEmulator,
+    downstream_emulator: Option<NetworkEmulator>,
+}
+
+impl UdpImpairmentProxy {
+    pub async fn bind(
+        address: impl ToSocketAddrs,
+        upstream: SocketAddr,
+        profile: ImpairmentProfile,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            socket: Arc::new(UdpSocket::bind(address).await?),
+            upstream,
+            upstream_emulator: NetworkEmulator::new(profile),
+            downstream_emulator: None,
+        })
+    }
+
+    /// Impairs the datagrams sent by the upstream to the client as well
+    pub fn downstream(mut self, profile: ImpairmentProfile) -> Self {
+        self.downstream_emulator = Some(NetworkEmulator::new(profile));
+        self
+    }
+
+    /// Address the client has to send its datagrams to
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    pub async fn run(mut self) {
+        let mut packet_buffer = vec![0; MAX_DATAGRAM_SIZE];
+        let mut client: Option<SocketAddr> = None;
+        let mut errors = ErrorBackoff::default();
+
+        loop {
+            let (packet_size, source) = match self.socket.recv_from(&mut packet_buffer).await {
+                Ok(received) => {
+                    errors.reset();
+                    received
+                }
+                Err(error) => {
+                    debug!("Unable to receive datagram: {}", error);
+                    errors.wait().await;
+                    continue;
+                }
+            };
+
+            let (destination, emulator) = if source == self.upstream {
+                match client {
+                    Some(client) => (client, self.downstream_emulator.as_mut()),
+                    None => {
+                        debug!("Dropping upstream datagram, no client to relay it to");
+                        continue;
+                    }
+                }
+            } else {
+                client = Some(source);
+                (self.upstream, Some(&mut self.upstream_emulator))
+            };
+
+            let packet = packet_buffer[..packet_size].to_vec();
+            let deliveries = match emulator {
+                Some(emulator) => emulator.schedule(packet_size, Instant::now()),
+                None => vec![Instant::now()],
+            };
+
+            for delivery in deliveries {
+                let socket = self.socket.clone();
+                let packet = packet.clone();
+
+                tokio::spawn(async move {
+                    tokio::time::sleep_until(delivery.into()).await;
+                    if let Err(error) = socket.send_to(&packet, destination).await {
+                        warn!("Unable to relay datagram to {}: {}", destination, error);
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::{io, path::Path, time::Duration};
+
+use super::assert_probability;
+
+/// Network conditions holding from `time` until the following step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceStep {
+    pub time: Duration,
+    pub delay: Duration,
+    pub jitter: Duration,
+    pub loss: f64,
+    pub bandwidth: Option<u64>,
+}
+
+/// Sequence of network conditions, parsed from a text file with one step per line:
+///
+/// ```text
+/// # time_ms delay_ms jitter_ms loss bandwidth_kbps
+/// 0     20  2  0.0   0
+/// 5000  80  10 0.05  2000
+/// ```
+///
+/// Lines starting with `#` are ignored, while a bandwidth of zero leaves the link uncapped.
+/// Steps must be sorted by time and the last one holds until the end of the emulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpairmentTrace {
+    steps: Vec<TraceStep>,
+}
+
+impl ImpairmentTrace {
+    pub fn new(steps: Vec<TraceStep>) -> Self {
+        assert!(!steps.is_empty(), "Trace must have at least one step");
+        assert!(
+            steps.windows(2).all(|steps| steps[0].time <= steps[1].time),
+            "Trace steps must be sorted by time"
+        );
+        for step in &steps {
+            assert_probability("Loss", step.loss);
+        }
+
+        Self { steps }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let mut steps = Vec::new();
+
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let step = parse_step(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid trace step at line {}: {}", line_index + 1, line),
+                )
+            })?;
+
+            if steps
+                .last()
+                .is_some_and(|last: &TraceStep| last.time > step.time)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsorted trace step at line {}", line_index + 1),
+                ));
+            }
+
+            steps.push(step);
+        }
+
+        if steps.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Empty trace"));
+        }
+
+        Ok(Self { steps })
+    }
+
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// Step holding at `elapsed` since the start of the emulation
+    pub fn step_at(&self, elapsed: Duration) -> &TraceStep {
+        let index = self.steps.partition_point(|step| step.time <= elapsed);
+        &self.steps[index.saturating_sub(1)]
+    }
+}
+
+fn parse_step(line: &str) -> Option<TraceStep> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 5 {
+        return None;
+    }
+
+    let millis = |field: &str| field.parse().ok().map(Duration::from_millis);
+
+    let loss: f64 = fields[3].parse().ok()?;
+    if !(0.0..=1.0).contains(&loss) {
+        return None;
+    }
+
+    let bandwidth_kbps: u64 = fields[4].parse().ok()?;
+
+    Some(TraceStep {
+        time: millis(fields[0])?,
+        delay: millis(fields[1])?,
+        jitter: millis(fields[2])?,
+        loss,
+        bandwidth: (bandwidth_kbps > 0).then_some(bandwidth_kbps * 1000),
+    })
+}
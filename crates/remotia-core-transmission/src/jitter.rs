@@ -0,0 +1,322 @@
+//! Jitter buffer: frames are held until a playout time which absorbs the variations of the
+//! network delay, then released in order at the pace they have been produced.
+//!
+//! The playout time of a frame is its media time (capture timestamp or sequence number) shifted
+//! by the lowest transit time observed lately plus a target delay. The target delay adapts to
+//! the transit times of the last frames: it grows as soon as they get more spread, and shrinks
+//! slowly once they settle down.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::debug;
+use remotia_core::{
+    error::DropReason,
+    traits::{FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::sync::Notify;
+
+/// Number of transit times the target delay is computed on
+const TRANSIT_WINDOW: usize = 128;
+
+/// Fraction of the frames the target delay is meant to let in on time
+const TARGET_PERCENTILE: f64 = 0.95;
+
+/// Weight of the new value when the target delay shrinks
+const DELAY_DECREASE_WEIGHT: f64 = 1.0 / 16.0;
+
+#[derive(Debug, Clone, Copy)]
+enum MediaClock<P> {
+    Timestamp(P),
+    Sequence(P, Duration),
+}
+
+struct HeldFrame<F> {
+    playout: Instant,
+    arrival: Instant,
+    frame_data: F,
+}
+
+struct JitterState<F> {
+    held: BTreeMap<u128, HeldFrame<F>>,
+    last_released: Option<u128>,
+
+    transits: VecDeque<i128>,
+    target_delay: Duration,
+}
+
+struct Shared<F> {
+    state: Mutex<JitterState<F>>,
+    released: Notify,
+    started_at: Instant,
+}
+
+/// Configuration of a jitter buffer, split into its input and output processors
+pub struct JitterBuffer<P> {
+    media_clock: MediaClock<P>,
+    min_delay: Duration,
+    max_delay: Duration,
+    capacity: usize,
+    delay_key: Option<P>,
+}
+
+impl<P: Copy> JitterBuffer<P> {
+    /// Orders the frames by the capture timestamp of the given property, in milliseconds
+    pub fn timestamp(key: P) -> Self {
+        Self::new(MediaClock::Timestamp(key))
+    }
+
+    /// Orders the frames by the sequence number of the given property, each frame lasting
+    /// `frame_interval`
+    pub fn sequence(key: P, frame_interval: Duration) -> Self {
+        Self::new(MediaClock::Sequence(key, frame_interval))
+    }
+
+    fn new(media_clock: MediaClock<P>) -> Self {
+        Self {
+            media_clock,
+            min_delay: Duration::ZERO,
+            max_delay: Duration::from_millis(500),
+            capacity: 64,
+            delay_key: None,
+        }
+    }
+
+    /// Sets the range the target delay is kept within (0 to 500ms by default)
+    pub fn delay_range(mut self, min_delay: Duration, max_delay: Duration) -> Self {
+        assert!(min_delay <= max_delay, "Invalid delay range");
+        self.min_delay = min_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the maximum number of held frames (64 by default)
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the key of the property the time each frame has been held for is written to, in
+    /// microseconds
+    pub fn delay_key(mut self, key: P) -> Self {
+        self.delay_key = Some(key);
+        self
+    }
+
+    /// Creates the processors the frames enter and leave the buffer through
+    pub fn split<F>(self) -> (JitterBufferInput<F, P>, JitterBufferOutput<F, P>) {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(JitterState {
+                held: BTreeMap::new(),
+                last_released: None,
+                transits: VecDeque::new(),
+                target_delay: self.min_delay,
+            }),
+            released: Notify::new(),
+            started_at: Instant::now(),
+        });
+
+        let input = JitterBufferInput {
+            media_clock: self.media_clock,
+            min_delay: self.min_delay,
+            max_delay: self.max_delay,
+            capacity: self.capacity,
+            shared: shared.clone(),
+        };
+        let output = JitterBufferOutput {
+            delay_key: self.delay_key,
+            shared,
+        };
+
+        (input, output)
+    }
+}
+
+/// Holds the received frames until their playout time.
+///
+/// Frames arriving after their playout time or after a later frame has been released are
+/// reported as `DropReason::StaleFrame` and passed on, as well as the frames evicted when the
+/// buffer is full. Frames without a media time are passed on as well, reported as
+/// `DropReason::InvalidPacketHeader` unless already dropped upstream. Held frames are consumed,
+/// to be released by the `JitterBufferOutput`.
+pub struct JitterBufferInput<F, P> {
+    media_clock: MediaClock<P>,
+    min_delay: Duration,
+    max_delay: Duration,
+    capacity: usize,
+    shared: Arc<Shared<F>>,
+}
+
+impl<F, P> JitterBufferInput<F, P> {
+    pub fn target_delay(&self) -> Duration {
+        self.shared.state.lock().unwrap().target_delay
+    }
+}
+
+impl<F, P: Copy> JitterBufferInput<F, P>
+where
+    F: FrameProperties<P, u128>,
+{
+    /// Media time of a frame in microseconds
+    fn media_time(&self, frame_data: &F) -> Option<u128> {
+        match self.media_clock {
+            MediaClock::Timestamp(key) => frame_data.get(&key).map(|timestamp| timestamp * 1000),
+            MediaClock::Sequence(key, frame_interval) => frame_data
+                .get(&key)
+                .map(|sequence| sequence * frame_interval.as_micros()),
+        }
+    }
+}
+
+#[async_trait]
+impl<F, P> FrameProcessor<F> for JitterBufferInput<F, P>
+where
+    P: Copy + Send,
+    F: FrameProperties<P, u128> + FrameError<DropReason> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let media_time = match self.media_time(&frame_data) {
+            Some(media_time) => media_time,
+            None => {
+                if frame_data.get_error().is_none() {
+                    debug!("Frame without media time");
+                    frame_data.report_error(DropReason::InvalidPacketHeader);
+                }
+                return Some(frame_data);
+            }
+        };
+
+        let arrival = Instant::now();
+        let arrival_offset = arrival.duration_since(self.shared.started_at).as_micros() as i128;
+        let transit = arrival_offset - media_time as i128;
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        state.transits.push_back(transit);
+        if state.transits.len() > TRANSIT_WINDOW {
+            state.transits.pop_front();
+        }
+        let min_transit = *state.transits.iter().min().unwrap();
+
+        let mut spreads: Vec<i128> = state
+            .transits
+            .iter()
+            .map(|transit| transit - min_transit)
+            .collect();
+        spreads.sort_unstable();
+        let percentile = ((spreads.len() - 1) as f64 * TARGET_PERCENTILE).round() as usize;
+        let desired_delay =
+            Duration::from_micros(spreads[percentile] as u64).clamp(self.min_delay, self.max_delay);
+
+        state.target_delay = if desired_delay >= state.target_delay {
+            desired_delay
+        } else {
+            state.target_delay.mul_f64(1.0 - DELAY_DECREASE_WEIGHT)
+                + desired_delay.mul_f64(DELAY_DECREASE_WEIGHT)
+        };
+
+        let playout_offset =
+            media_time as i128 + min_transit + state.target_delay.as_micros() as i128;
+        let playout = self.shared.started_at + Duration::from_micros(playout_offset.max(0) as u64);
+
+        let already_released = state
+            .last_released
+            .is_some_and(|last_released| media_time <= last_released);
+        if already_released
+            || state.held.contains_key(&media_time)
+            || playout_offset < arrival_offset
+        {
+            debug!(
+                "Stale frame (media time: {}us, {}us late)",
+                media_time,
+                (arrival_offset - playout_offset).max(0)
+            );
+            frame_data.report_error(DropReason::StaleFrame);
+            return Some(frame_data);
+        }
+
+        state.held.insert(
+            media_time,
+            HeldFrame {
+                playout,
+                arrival,
+                frame_data,
+            },
+        );
+
+        let evicted = if state.held.len() > self.capacity {
+            let (media_time, evicted) = state.held.pop_first().unwrap();
+            debug!("Jitter buffer full, evicting frame {}us", media_time);
+            state.last_released = Some(media_time);
+
+            let mut frame_data = evicted.frame_data;
+            frame_data.report_error(DropReason::StaleFrame);
+            Some(frame_data)
+        } else {
+            None
+        };
+
+        drop(state);
+        self.shared.released.notify_one();
+
+        evicted
+    }
+}
+
+/// Releases the held frames in order, each one at its playout time. Being the source of the
+/// frames, it is meant to be the first processor of a component without predecessors, the
+/// frame it is given being discarded.
+pub struct JitterBufferOutput<F, P> {
+    delay_key: Option<P>,
+    shared: Arc<Shared<F>>,
+}
+
+impl<F, P> JitterBufferOutput<F, P> {
+    pub fn held_frames(&self) -> usize {
+        self.shared.state.lock().unwrap().held.len()
+    }
+}
+
+#[async_trait]
+impl<F, P> FrameProcessor<F> for JitterBufferOutput<F, P>
+where
+    P: Copy + Send,
+    F: FrameProperties<P, u128> + Send + 'static,
+{
+    async fn process(&mut self, _frame_data: F) -> Option<F> {
+        loop {
+            let notified = self.shared.released.notified();
+
+            let next_playout = {
+                let mut state = self.shared.state.lock().unwrap();
+                match state.held.first_key_value() {
+                    Some((_, next)) if next.playout <= Instant::now() => {
+                        let (media_time, released) = state.held.pop_first().unwrap();
+                        state.last_released = Some(media_time);
+                        drop(state);
+
+                        let mut frame_data = released.frame_data;
+                        if let Some(delay_key) = self.delay_key {
+                            frame_data.set(delay_key, released.arrival.elapsed().as_micros());
+                        }
+                        return Some(frame_data);
+                    }
+                    Some((_, next)) => Some(next.playout),
+                    None => None,
+                }
+            };
+
+            match next_playout {
+                Some(playout) => {
+                    let _ = tokio::time::timeout_at(playout.into(), notified).await;
+                }
+                None => notified.await,
+            }
+        }
+    }
+}
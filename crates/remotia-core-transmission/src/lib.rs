@@ -1,2 +1,25 @@
-pub mod sender;
+pub mod broadcast;
+#[cfg(any(feature = "quic", feature = "tls"))]
+pub mod certificates;
+pub mod clock_sync;
+pub mod congestion;
+pub mod connection;
+pub mod framing;
+pub mod impairment;
+pub mod jitter;
+pub mod loopback;
+pub mod pacing;
+#[cfg(feature = "quic")]
+pub mod quic;
 pub mod receiver;
+pub mod remvsp;
+pub mod sender;
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(unix)]
+pub mod unix;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,264 @@
+//! In-process link between two pipelines, optionally impaired to emulate a network. Frames are
+//! delivered in order of their emulated arrival time, which makes end-to-end tests
+//! deterministic when the impairment is seeded.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{
+        BorrowFrameProperties, BorrowMutFrameProperties, FrameError, FrameProcessor,
+        FrameProperties,
+    },
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    framing::{FrameHeader, HeaderKeys},
+    impairment::{ImpairmentProfile, NetworkEmulator},
+};
+
+struct Packet {
+    delivery: Instant,
+    sequence: u64,
+    header: FrameHeader,
+    data: Vec<u8>,
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.delivery, self.sequence).cmp(&(other.delivery, other.sequence))
+    }
+}
+
+/// Sending half of a link, see `link`
+pub struct LinkSender(mpsc::Sender<Packet>);
+
+/// Receiving half of a link, see `link`
+pub struct LinkReceiver(mpsc::Receiver<Packet>);
+
+/// Creates a link holding up to `capacity` frames in flight
+pub fn link(capacity: usize) -> (LinkSender, LinkReceiver) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    (LinkSender(sender), LinkReceiver(receiver))
+}
+
+/// Sends the content of a buffer through a link, along with the frame id, timestamp and flags
+/// read from the frame properties
+pub struct LoopbackFrameSender<K, P> {
+    buffer_key: K,
+    header_keys: HeaderKeys<Option<P>>,
+
+    link: LinkSender,
+    emulator: Option<NetworkEmulator>,
+    next_sequence: u64,
+}
+
+impl<K, P> LoopbackFrameSender<K, P> {
+    pub fn new(buffer_key: K, link: LinkSender) -> Self {
+        Self {
+            buffer_key,
+            header_keys: HeaderKeys::default(),
+            link,
+            emulator: None,
+            next_sequence: 0,
+        }
+    }
+
+    /// Delays, drops, duplicates or reorders the frames according to the profile
+    pub fn impairment(mut self, profile: ImpairmentProfile) -> Self {
+        self.emulator = Some(NetworkEmulator::new(profile));
+        self
+    }
+
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for LoopbackFrameSender<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        let mut header = match FrameHeader::new(buffer.len()) {
+            Ok(header) => header,
+            Err(_) => {
+                warn!("Frame of {} bytes is too large to be sent", buffer.len());
+                frame_data.report_error(DropReason::OversizedFrame);
+                return Some(frame_data);
+            }
+        };
+        self.header_keys.fill(&mut header, &frame_data);
+
+        let now = Instant::now();
+        let deliveries = match &mut self.emulator {
+            Some(emulator) => emulator.schedule(buffer.len(), now),
+            None => vec![now],
+        };
+
+        let packets: Vec<Packet> = deliveries
+            .into_iter()
+            .map(|delivery| {
+                self.next_sequence += 1;
+                Packet {
+                    delivery,
+                    sequence: self.next_sequence,
+                    header,
+                    data: buffer.to_vec(),
+                }
+            })
+            .collect();
+
+        for packet in packets {
+            if self.link.0.send(packet).await.is_err() {
+                debug!("Link closed");
+                frame_data.report_error(DropReason::ConnectionError);
+                break;
+            }
+        }
+
+        Some(frame_data)
+    }
+}
+
+/// Receives the frames sent by a `LoopbackFrameSender`, each one once its delivery time is
+/// reached. `DropReason::ConnectionError` is reported once the sender is gone and all the
+/// frames in flight have been received.
+pub struct LoopbackFrameReceiver<K, P> {
+    buffer_key: K,
+    header_keys: HeaderKeys<Option<P>>,
+
+    link: LinkReceiver,
+    in_flight: BinaryHeap<Reverse<Packet>>,
+    closed: bool,
+}
+
+impl<K, P> LoopbackFrameReceiver<K, P> {
+    pub fn new(buffer_key: K, link: LinkReceiver) -> Self {
+        Self {
+            buffer_key,
+            header_keys: HeaderKeys::default(),
+            link,
+            in_flight: BinaryHeap::new(),
+            closed: false,
+        }
+    }
+
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
+
+    /// Waits for the next packet to be due, collecting the ones sent in the meantime
+    async fn next_packet(&mut self) -> Option<Packet> {
+        loop {
+            while let Ok(packet) = self.link.0.try_recv() {
+                self.in_flight.push(Reverse(packet));
+            }
+
+            let next_delivery = self.in_flight.peek().map(|packet| packet.0.delivery);
+            match next_delivery {
+                Some(delivery) if delivery <= Instant::now() => {
+                    return self.in_flight.pop().map(|packet| packet.0);
+                }
+                Some(delivery) if self.closed => {
+                    tokio::time::sleep_until(delivery.into()).await;
+                }
+                Some(delivery) => {
+                    match tokio::time::timeout_at(delivery.into(), self.link.0.recv()).await {
+                        Ok(Some(packet)) => self.in_flight.push(Reverse(packet)),
+                        Ok(None) => self.closed = true,
+                        Err(_) => {}
+                    }
+                }
+                None if self.closed => return None,
+                None => match self.link.0.recv().await {
+                    Some(packet) => self.in_flight.push(Reverse(packet)),
+                    None => self.closed = true,
+                },
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for LoopbackFrameReceiver<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowMutFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let packet = match self.next_packet().await {
+            Some(packet) => packet,
+            None => {
+                frame_data.report_error(DropReason::ConnectionError);
+                return Some(frame_data);
+            }
+        };
+
+        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        buffer.clear();
+        buffer.extend_from_slice(&packet.data);
+
+        self.header_keys.apply(&packet.header, &mut frame_data);
+
+        Some(frame_data)
+    }
+}
@@ -0,0 +1,83 @@
+//! Pacing of the transmissions: the bytes of a frame are spread over time instead of being
+//! written in a single burst, which would build up the queues of the bottleneck links and cause
+//! losses, especially on large keyframes.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// Sends at most the given number of bits per second
+    Rate(u64),
+
+    /// Spreads the bytes of each frame over the given interval, e.g. the frame interval
+    Interval(Duration),
+}
+
+/// Schedules the packets of the frames according to a `Pacing`, keeping track of the delay it
+/// adds to each frame
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    pacing: Pacing,
+    bytes_per_second: f64,
+
+    next_send: Instant,
+    frame_started_at: Instant,
+    last_release: Instant,
+}
+
+impl Pacer {
+    pub fn new(pacing: Pacing) -> Self {
+        let bytes_per_second = match pacing {
+            Pacing::Rate(bits_per_second) => {
+                assert!(bits_per_second > 0, "Pacing rate must be positive");
+                bits_per_second as f64 / 8.0
+            }
+            Pacing::Interval(_) => f64::INFINITY,
+        };
+
+        let now = Instant::now();
+        Self {
+            pacing,
+            bytes_per_second,
+            next_send: now,
+            frame_started_at: now,
+            last_release: now,
+        }
+    }
+
+    /// Begins the transmission of a frame of the given size, in bytes
+    pub fn start_frame(&mut self, frame_size: usize) {
+        let now = Instant::now();
+        self.frame_started_at = now;
+        self.last_release = now;
+
+        if let Pacing::Interval(interval) = self.pacing {
+            self.bytes_per_second = if interval.is_zero() || frame_size == 0 {
+                f64::INFINITY
+            } else {
+                frame_size as f64 / interval.as_secs_f64()
+            };
+        }
+    }
+
+    /// Waits until a packet of the given size may be sent
+    pub async fn pace(&mut self, packet_size: usize) {
+        // The schedule is kept on the intended send times, so that the timer overshoots do not
+        // add up over the packets
+        let scheduled = if self.next_send > Instant::now() {
+            tokio::time::sleep_until(self.next_send.into()).await;
+            self.next_send
+        } else {
+            Instant::now()
+        };
+
+        self.last_release = Instant::now();
+        self.next_send =
+            scheduled + Duration::from_secs_f64(packet_size as f64 / self.bytes_per_second);
+    }
+
+    /// Time the packets of the current frame have been held back for
+    pub fn frame_delay(&self) -> Duration {
+        self.last_release.duration_since(self.frame_started_at)
+    }
+}
@@ -0,0 +1,38 @@
+use std::{io, net::SocketAddr, sync::Arc};
+
+use quinn::{rustls::RootCertStore, ClientConfig, Endpoint, ServerConfig};
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+
+/// Endpoint accepting connections on `address`, authenticated by the given certificate
+pub fn server_endpoint(
+    address: SocketAddr,
+    certificate: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+) -> io::Result<Endpoint> {
+    let config = ServerConfig::with_single_cert(vec![certificate], key)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+    Endpoint::server(config, address)
+}
+
+/// Endpoint bound to `address`, connecting to the servers authenticated by one of the trusted
+/// certificates
+pub fn client_endpoint(
+    address: SocketAddr,
+    trusted_certificates: &[CertificateDer<'static>],
+) -> io::Result<Endpoint> {
+    let mut roots = RootCertStore::empty();
+    for certificate in trusted_certificates {
+        roots
+            .add(certificate.clone())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    }
+
+    let config = ClientConfig::with_root_certificates(Arc::new(roots))
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+    let mut endpoint = Endpoint::client(address)?;
+    endpoint.set_default_client_config(config);
+
+    Ok(endpoint)
+}
@@ -0,0 +1,23 @@
+//! Transmission of frames over QUIC. Each buffer of a frame is sent either on its own
+//! unidirectional stream, reliably but without blocking the following frames on loss, or as
+//! RemVSP fragments carried by unreliable datagrams.
+//!
+//! Stream messages carry the index of the buffer, a `FrameHeader` and the buffer content, while
+//! datagrams carry the index of the buffer followed by an encoded `RemVSPFrameFragment`.
+
+pub mod endpoint;
+pub mod receiver;
+pub mod sender;
+
+/// Upper bound of the size of a frame received on a stream
+const MAX_STREAM_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// How the content of a buffer is delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuicDelivery {
+    /// Reliable, on a unidirectional stream opened for each frame
+    Stream,
+
+    /// Unreliable, fragmented in datagrams
+    Datagram,
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::debug;
+use quinn::Connection;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowMutFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{
+    framing::FrameHeader,
+    remvsp::{decode_fragment, reassembly::FrameReassembler},
+};
+
+use super::MAX_STREAM_FRAME_SIZE;
+
+/// How long the fragments of a frame received as datagrams are kept waiting for the others
+const FRAGMENTS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Content of a buffer of a frame, received either on a stream or as datagrams
+struct ReceivedBuffer {
+    buffer_index: usize,
+    frame_id: u64,
+    capture_timestamp: u128,
+    data: Vec<u8>,
+}
+
+struct PendingFrame {
+    capture_timestamp: u128,
+    buffers: Vec<Option<Vec<u8>>>,
+}
+
+/// Receives frames sent by a `QuicFrameSender`, writing the content of each buffer to the buffer
+/// added in the same position. Streams and datagrams are received concurrently, so that a frame
+/// is completed as soon as all its buffers have been received.
+///
+/// If no frame is completed within the timeout, `DropReason::NoCompleteFrames` is reported,
+/// while `DropReason::ConnectionError` is reported once the connection is closed. Incomplete
+/// frames older than a completed one are discarded.
+pub struct QuicFrameReceiver<K, P> {
+    buffer_keys: Vec<K>,
+    timestamp_key: Option<P>,
+    frame_id_key: Option<P>,
+    timeout: Duration,
+
+    connection: Connection,
+    received: Option<mpsc::UnboundedReceiver<ReceivedBuffer>>,
+    tasks: Vec<JoinHandle<()>>,
+
+    pending_frames: BTreeMap<u64, PendingFrame>,
+    last_completed_frame: Option<u64>,
+}
+
+impl<K, P> QuicFrameReceiver<K, P> {
+    pub fn new(buffer_key: K, connection: Connection) -> Self {
+        Self {
+            buffer_keys: vec![buffer_key],
+            timestamp_key: None,
+            frame_id_key: None,
+            timeout: Duration::from_millis(100),
+            connection,
+            received: None,
+            tasks: Vec::new(),
+            pending_frames: BTreeMap::new(),
+            last_completed_frame: None,
+        }
+    }
+
+    /// Receives an additional buffer of each frame, sent by the `QuicFrameSender` buffer added
+    /// in the same position
+    pub fn buffer(mut self, buffer_key: K) -> Self {
+        self.buffer_keys.push(buffer_key);
+        self
+    }
+
+    /// Sets how long to wait for a frame to be completed (100 ms by default)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the key of the property the capture timestamp of the frame is written to
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.timestamp_key = Some(key);
+        self
+    }
+
+    /// Sets the key of the property the id of the frame is written to
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.frame_id_key = Some(key);
+        self
+    }
+
+    fn start(&mut self) {
+        let (sender, received) = mpsc::unbounded_channel();
+        self.received = Some(received);
+
+        self.tasks.push(tokio::spawn(receive_streams(
+            self.connection.clone(),
+            sender.clone(),
+        )));
+        self.tasks.push(tokio::spawn(receive_datagrams(
+            self.connection.clone(),
+            sender,
+        )));
+    }
+
+    /// Stores a received buffer, returning the frame it completes if any
+    fn insert(&mut self, received: ReceivedBuffer) -> Option<(u64, PendingFrame)> {
+        let buffers_count = self.buffer_keys.len();
+        if received.buffer_index >= buffers_count
+            || self
+                .last_completed_frame
+                .is_some_and(|last_completed| received.frame_id <= last_completed)
+        {
+            return None;
+        }
+
+        let pending_frame = self
+            .pending_frames
+            .entry(received.frame_id)
+            .or_insert_with(|| PendingFrame {
+                capture_timestamp: received.capture_timestamp,
+                buffers: vec![None; buffers_count],
+            });
+        pending_frame.buffers[received.buffer_index] = Some(received.data);
+
+        if pending_frame.buffers.iter().any(Option::is_none) {
+            return None;
+        }
+
+        let frame_id = received.frame_id;
+        let completed_frame = self.pending_frames.remove(&frame_id).unwrap();
+
+        let newer_frames = self.pending_frames.split_off(&frame_id);
+        if !self.pending_frames.is_empty() {
+            debug!("Dropped {} incomplete frames", self.pending_frames.len());
+        }
+        self.pending_frames = newer_frames;
+        self.last_completed_frame = Some(frame_id);
+
+        Some((frame_id, completed_frame))
+    }
+}
+
+impl<K, P> Drop for QuicFrameReceiver<K, P> {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for QuicFrameReceiver<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowMutFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        if self.received.is_none() {
+            self.start();
+        }
+
+        let deadline = Instant::now() + self.timeout;
+
+        let (frame_id, completed_frame) = loop {
+            let received = self.received.as_mut().unwrap().recv();
+            let received = match tokio::time::timeout_at(deadline.into(), received).await {
+                Ok(Some(received)) => received,
+                Ok(None) => {
+                    frame_data.report_error(DropReason::ConnectionError);
+                    return Some(frame_data);
+                }
+                Err(_) => {
+                    frame_data.report_error(DropReason::NoCompleteFrames);
+                    return Some(frame_data);
+                }
+            };
+
+            if let Some(completed_frame) = self.insert(received) {
+                break completed_frame;
+            }
+        };
+
+        debug!("Received frame {}", frame_id);
+
+        for (buffer_key, data) in self.buffer_keys.iter().zip(completed_frame.buffers) {
+            let buffer = frame_data.get_mut_ref(buffer_key).unwrap();
+            buffer.clear();
+            buffer.extend_from_slice(&data.unwrap());
+        }
+
+        if let Some(timestamp_key) = self.timestamp_key {
+            frame_data.set(timestamp_key, completed_frame.capture_timestamp);
+        }
+
+        if let Some(frame_id_key) = self.frame_id_key {
+            frame_data.set(frame_id_key, frame_id as u128);
+        }
+
+        Some(frame_data)
+    }
+}
+
+async fn receive_streams(connection: Connection, sender: mpsc::UnboundedSender<ReceivedBuffer>) {
+    loop {
+        let mut stream = match connection.accept_uni().await {
+            Ok(stream) => stream,
+            Err(error) => {
+                debug!("Stopped accepting streams: {}", error);
+                return;
+            }
+        };
+
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            let message = match stream
+                .read_to_end(1 + FrameHeader::SIZE + MAX_STREAM_FRAME_SIZE)
+                .await
+            {
+                Ok(message) if message.len() > FrameHeader::SIZE => message,
+                Ok(message) => {
+                    debug!("Invalid stream message of {} bytes", message.len());
+                    return;
+                }
+                Err(error) => {
+                    debug!("Unable to read stream: {}", error);
+                    return;
+                }
+            };
+
+            let header = FrameHeader::decode(message[1..][..FrameHeader::SIZE].try_into().unwrap());
+            let data = message[1 + FrameHeader::SIZE..].to_vec();
+            if data.len() != header.length as usize {
+                debug!("Invalid stream message for frame {}", header.frame_id);
+                return;
+            }
+
+            sender
+                .send(ReceivedBuffer {
+                    buffer_index: message[0] as usize,
+                    frame_id: header.frame_id,
+                    capture_timestamp: header.timestamp as u128,
+                    data,
+                })
+                .ok();
+        });
+    }
+}
+
+async fn receive_datagrams(connection: Connection, sender: mpsc::UnboundedSender<ReceivedBuffer>) {
+    let mut reassemblers: HashMap<u8, FrameReassembler> = HashMap::new();
+
+    loop {
+        let datagram = match connection.read_datagram().await {
+            Ok(datagram) => datagram,
+            Err(error) => {
+                debug!("Stopped receiving datagrams: {}", error);
+                return;
+            }
+        };
+
+        let fragment = match datagram.split_first() {
+            Some((buffer_index, packet)) => {
+                decode_fragment(packet).map(|fragment| (*buffer_index, fragment))
+            }
+            None => None,
+        };
+
+        let (buffer_index, fragment) = match fragment {
+            Some(fragment) => fragment,
+            None => {
+                debug!("Invalid datagram of {} bytes", datagram.len());
+                continue;
+            }
+        };
+
+        let now = Instant::now();
+        let reassembler = reassemblers.entry(buffer_index).or_default();
+        reassembler.expire(now, FRAGMENTS_TIMEOUT);
+
+        match reassembler.insert(fragment, now) {
+            Ok(Some(completed_frame)) => {
+                sender
+                    .send(ReceivedBuffer {
+                        buffer_index: buffer_index as usize,
+                        frame_id: completed_frame.header.frame_id,
+                        capture_timestamp: completed_frame.header.capture_timestamp,
+                        data: completed_frame.data,
+                    })
+                    .ok();
+            }
+            Ok(None) => {}
+            Err(error) => debug!("Invalid fragment: {}", error),
+        }
+    }
+}
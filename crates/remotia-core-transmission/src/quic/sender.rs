@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use log::{debug, warn};
+use quinn::Connection;
+use remotia_buffer_utils::{Bytes, BytesMut};
+use remotia_core::{
+    common::network::remvsp::{RemVSPFrameFragment, RemVSPFrameHeader},
+    error::DropReason,
+    traits::{BorrowFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+
+use crate::{
+    framing::FrameHeader,
+    remvsp::{encode_fragment, FRAGMENT_OVERHEAD},
+};
+
+use super::QuicDelivery;
+
+enum Message {
+    Stream(Vec<u8>),
+    Datagrams(Vec<Bytes>),
+}
+
+/// Sends the content of one or more buffers of each frame over a QUIC connection, each one
+/// with its own delivery mode. Frames which cannot be sent are reported as
+/// `DropReason::ConnectionError`.
+pub struct QuicFrameSender<K, P> {
+    buffers: Vec<(K, QuicDelivery)>,
+    timestamp_key: Option<P>,
+    frame_id_key: Option<P>,
+
+    connection: Connection,
+    next_frame_id: u64,
+}
+
+impl<K, P> QuicFrameSender<K, P> {
+    pub fn new(buffer_key: K, delivery: QuicDelivery, connection: Connection) -> Self {
+        Self {
+            buffers: vec![(buffer_key, delivery)],
+            timestamp_key: None,
+            frame_id_key: None,
+            connection,
+            next_frame_id: 0,
+        }
+    }
+
+    /// Sends an additional buffer of each frame, received by the `QuicFrameReceiver` buffer
+    /// added in the same position
+    pub fn buffer(mut self, buffer_key: K, delivery: QuicDelivery) -> Self {
+        assert!(self.buffers.len() < u8::MAX as usize, "Too many buffers");
+        self.buffers.push((buffer_key, delivery));
+        self
+    }
+
+    /// Sets the key of the property whose value is sent as the capture timestamp
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.timestamp_key = Some(key);
+        self
+    }
+
+    /// Sets the key of the property the id assigned to each frame is written to
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.frame_id_key = Some(key);
+        self
+    }
+
+    fn datagrams(
+        &self,
+        buffer_index: u8,
+        buffer: &[u8],
+        frame_id: u64,
+        capture_timestamp: u128,
+    ) -> Option<Vec<Bytes>> {
+        let fragment_size = self
+            .connection
+            .max_datagram_size()?
+            .checked_sub(FRAGMENT_OVERHEAD + 1)
+            .filter(|fragment_size| *fragment_size > 0)?
+            .min(u16::MAX as usize);
+
+        let chunks: Vec<&[u8]> = buffer.chunks(fragment_size).collect();
+        let chunks = if chunks.is_empty() {
+            vec![buffer]
+        } else {
+            chunks
+        };
+
+        if chunks.len() > u16::MAX as usize {
+            return None;
+        }
+
+        let frame_header = RemVSPFrameHeader {
+            frame_id,
+            frame_fragments_count: chunks.len() as u16,
+            fragment_size: fragment_size as u16,
+            frame_length: buffer.len() as u32,
+            fec_group_size: 0,
+            capture_timestamp,
+        };
+
+        let datagrams = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(fragment_id, data)| {
+                let mut datagram = vec![buffer_index];
+                datagram.extend(encode_fragment(&RemVSPFrameFragment {
+                    frame_header,
+                    fragment_id: fragment_id as u16,
+                    data: data.to_vec(),
+                }));
+                Bytes::from(datagram)
+            })
+            .collect();
+
+        Some(datagrams)
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for QuicFrameSender<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let frame_id = self.next_frame_id;
+        self.next_frame_id += 1;
+
+        if let Some(frame_id_key) = self.frame_id_key {
+            frame_data.set(frame_id_key, frame_id as u128);
+        }
+
+        let capture_timestamp = self
+            .timestamp_key
+            .and_then(|key| frame_data.get(&key))
+            .unwrap_or(0);
+
+        let mut messages = Vec::with_capacity(self.buffers.len());
+        for (buffer_index, (buffer_key, delivery)) in self.buffers.iter().enumerate() {
+            let buffer = frame_data.get_ref(buffer_key).unwrap();
+
+            let message = match delivery {
+                QuicDelivery::Stream => {
+                    let header = match FrameHeader::new(buffer.len()) {
+                        Ok(header) => FrameHeader {
+                            frame_id,
+                            timestamp: capture_timestamp as u64,
+                            ..header
+                        },
+                        Err(_) => {
+                            warn!("Frame of {} bytes is too large to be sent", buffer.len());
+                            frame_data.report_error(DropReason::OversizedFrame);
+                            return Some(frame_data);
+                        }
+                    };
+
+                    let mut message = Vec::with_capacity(1 + FrameHeader::SIZE + buffer.len());
+                    message.push(buffer_index as u8);
+                    message.extend_from_slice(&header.encode());
+                    message.extend_from_slice(buffer);
+                    Message::Stream(message)
+                }
+                QuicDelivery::Datagram => {
+                    match self.datagrams(buffer_index as u8, buffer, frame_id, capture_timestamp) {
+                        Some(datagrams) => Message::Datagrams(datagrams),
+                        None => {
+                            warn!(
+                                "Unable to send buffer of {} bytes as datagrams",
+                                buffer.len()
+                            );
+                            frame_data.report_error(DropReason::ConnectionError);
+                            return Some(frame_data);
+                        }
+                    }
+                }
+            };
+
+            messages.push(message);
+        }
+
+        debug!("Sending frame {} ({} buffers)", frame_id, messages.len());
+
+        for message in messages {
+            if let Err(error) = send(&self.connection, message).await {
+                warn!("Unable to send frame {}: {}", frame_id, error);
+                frame_data.report_error(DropReason::ConnectionError);
+                break;
+            }
+        }
+
+        Some(frame_data)
+    }
+}
+
+async fn send(connection: &Connection, message: Message) -> Result<(), String> {
+    match message {
+        Message::Stream(message) => {
+            let mut stream = connection
+                .open_uni()
+                .await
+                .map_err(|error| error.to_string())?;
+            stream
+                .write_all(&message)
+                .await
+                .map_err(|error| error.to_string())?;
+            stream.finish().map_err(|error| error.to_string())
+        }
+        Message::Datagrams(datagrams) => {
+            for datagram in datagrams {
+                connection
+                    .send_datagram(datagram)
+                    .map_err(|error| error.to_string())?;
+            }
+
+            Ok(())
+        }
+    }
+}
@@ -1,30 +1,158 @@
+use std::io;
+
 use async_trait::async_trait;
 
+use log::warn;
 use remotia_buffer_utils::BytesMut;
-use remotia_core::traits::{FrameProcessor, BorrowMutFrameProperties};
-use tokio::io::AsyncReadExt;
+use remotia_core::error::DropReason;
+use remotia_core::traits::{
+    BorrowMutFrameProperties, FrameError, FrameProcessor, OptionalPropertyKey,
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+use crate::connection::{ConnectionManager, ConnectionState};
+use crate::framing::{FrameHeader, HeaderKeys};
+
+const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
 
-pub struct TcpFrameReceiver<K> {
+/// Receives frames sent by a `TcpFrameSender`, resizing the buffer to the length in the header.
+/// The frame id, timestamp and flags are only written to the frame properties whose keys are set.
+///
+/// Frames larger than the maximum size are skipped and reported as
+/// `DropReason::InvalidWholeFrameHeader`, while frames lost because the connection is down are
+/// reported as `DropReason::ConnectionError`.
+pub struct TcpFrameReceiver<K, P = (), S = TcpStream> {
     buffer_key: K,
-    socket: TcpStream,
+    header_keys: HeaderKeys<P>,
+    max_frame_size: usize,
+    connection: ConnectionManager<S>,
+}
+
+impl<K, S: Send + 'static> TcpFrameReceiver<K, (), S> {
+    pub fn new(buffer_key: K, socket: S) -> Self {
+        Self::with_connection(buffer_key, ConnectionManager::from_stream(socket))
+    }
+
+    /// Receives the frames over a connection which is re-established whenever lost
+    pub fn with_connection(buffer_key: K, connection: ConnectionManager<S>) -> Self {
+        Self {
+            buffer_key,
+            header_keys: HeaderKeys::new(),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            connection,
+        }
+    }
+
+    pub fn frame_id_key<P>(self, key: P) -> TcpFrameReceiver<K, Option<P>, S> {
+        self.typed().frame_id_key(key)
+    }
+
+    pub fn timestamp_key<P>(self, key: P) -> TcpFrameReceiver<K, Option<P>, S> {
+        self.typed().timestamp_key(key)
+    }
+
+    pub fn flags_key<P>(self, key: P) -> TcpFrameReceiver<K, Option<P>, S> {
+        self.typed().flags_key(key)
+    }
+
+    fn typed<P>(self) -> TcpFrameReceiver<K, Option<P>, S> {
+        TcpFrameReceiver {
+            buffer_key: self.buffer_key,
+            header_keys: self.header_keys.typed(),
+            max_frame_size: self.max_frame_size,
+            connection: self.connection,
+        }
+    }
+}
+
+impl<K, P, S> TcpFrameReceiver<K, Option<P>, S> {
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
 }
 
-impl<K> TcpFrameReceiver<K> {
-    pub fn new(buffer_key: K, socket: TcpStream) -> Self {
-        Self { buffer_key, socket }
+impl<K, P, S: Send + 'static> TcpFrameReceiver<K, P, S> {
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection.state()
+    }
+
+    /// Sets the maximum accepted frame length in bytes (16 MiB by default)
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
     }
 }
 
 #[async_trait]
-impl<F, K> FrameProcessor<F> for TcpFrameReceiver<K>
+impl<F, K, P, S> FrameProcessor<F> for TcpFrameReceiver<K, P, S>
 where
     K: Send,
-    F: BorrowMutFrameProperties<K, BytesMut> + Send + 'static,
+    P: OptionalPropertyKey<F, u128>,
+    S: AsyncRead + Unpin + Send + 'static,
+    F: BorrowMutFrameProperties<K, BytesMut> + FrameError<DropReason> + Send + 'static,
 {
     async fn process(&mut self, mut frame_data: F) -> Option<F> {
-        let mut buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
-        self.socket.read_exact(&mut buffer).await.unwrap();
+        let socket = match self.connection.wait_stream().await {
+            Some(socket) => socket,
+            None => {
+                frame_data.report_error(DropReason::ConnectionError);
+                return Some(frame_data);
+            }
+        };
+
+        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        match receive_frame(socket, buffer, self.max_frame_size).await {
+            Ok(Some(header)) => self.header_keys.apply(&header, &mut frame_data),
+            Ok(None) => frame_data.report_error(DropReason::InvalidWholeFrameHeader),
+            Err(error) => {
+                self.connection.disconnect(&error);
+                frame_data.report_error(DropReason::ConnectionError);
+            }
+        }
+
         Some(frame_data)
     }
 }
+
+/// Reads the next frame into the buffer, returning its header or none if the frame has been
+/// skipped for exceeding the maximum size
+async fn receive_frame<S: AsyncRead + Unpin>(
+    socket: &mut S,
+    buffer: &mut BytesMut,
+    max_frame_size: usize,
+) -> io::Result<Option<FrameHeader>> {
+    let mut header_buffer = [0; FrameHeader::SIZE];
+    socket.read_exact(&mut header_buffer).await?;
+    let header = FrameHeader::decode(&header_buffer);
+
+    let length = header.length as usize;
+    if length > max_frame_size {
+        warn!(
+            "Skipping frame of {} bytes, exceeding the maximum size of {} bytes",
+            length, max_frame_size
+        );
+
+        let mut payload = socket.take(length as u64);
+        tokio::io::copy(&mut payload, &mut tokio::io::sink()).await?;
+        return Ok(None);
+    }
+
+    buffer.clear();
+    buffer.resize(length, 0);
+    socket.read_exact(buffer).await?;
+
+    Ok(Some(header))
+}
@@ -0,0 +1,47 @@
+//! XOR parity forward error correction: the data fragments of a frame are split into groups,
+//! each one followed by a parity fragment which allows to recover any single missing fragment of
+//! the group.
+
+use std::ops::Range;
+
+use remotia_core::common::network::remvsp::RemVSPFrameHeader;
+
+pub fn parity_fragments_count(header: &RemVSPFrameHeader) -> usize {
+    match header.fec_group_size {
+        0 => 0,
+        group_size => (header.frame_fragments_count as usize).div_ceil(group_size as usize),
+    }
+}
+
+/// Range of the ids of the data fragments protected by a parity fragment
+pub fn group_range(header: &RemVSPFrameHeader, group: usize) -> Range<usize> {
+    let group_size = header.fec_group_size as usize;
+    let data_fragments = header.frame_fragments_count as usize;
+    (group * group_size).min(data_fragments)..((group + 1) * group_size).min(data_fragments)
+}
+
+/// Expected length of a fragment, shorter than the fragment size only for the last data one
+pub fn fragment_length(header: &RemVSPFrameHeader, fragment_id: usize) -> usize {
+    let data_fragments = header.frame_fragments_count as usize;
+    let fragment_size = header.fragment_size as usize;
+
+    if fragment_id + 1 == data_fragments {
+        header.frame_length as usize - (data_fragments - 1) * fragment_size
+    } else {
+        fragment_size
+    }
+}
+
+/// XORs the source into the destination, as if the source was padded with zeros
+pub fn xor_into(destination: &mut [u8], source: &[u8]) {
+    destination
+        .iter_mut()
+        .zip(source)
+        .for_each(|(destination, source)| *destination ^= source);
+}
+
+pub fn parity<'a>(fragments: impl Iterator<Item = &'a [u8]>, fragment_size: usize) -> Vec<u8> {
+    let mut parity = vec![0; fragment_size];
+    fragments.for_each(|fragment| xor_into(&mut parity, fragment));
+    parity
+}
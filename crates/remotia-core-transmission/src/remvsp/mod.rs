@@ -0,0 +1,28 @@
+//! RemVSP, a minimal protocol to stream frames over UDP: each frame is split into fragments
+//! fitting a datagram, each one carrying the header of the whole frame.
+
+use remotia_core::common::network::remvsp::RemVSPFrameFragment;
+
+pub mod fec;
+pub mod nack;
+pub mod reassembly;
+pub mod receiver;
+pub mod sender;
+
+/// Upper bound of the space taken by the fragment header and fields once encoded
+pub const FRAGMENT_OVERHEAD: usize = 64;
+
+/// Default maximum size of the sent datagrams
+pub const DEFAULT_MTU: usize = 1400;
+
+pub(crate) const MAX_DATAGRAM_SIZE: usize = 65536;
+
+pub(crate) fn encode_fragment(fragment: &RemVSPFrameFragment) -> Vec<u8> {
+    bincode::serde::encode_to_vec(fragment, bincode::config::standard()).unwrap()
+}
+
+pub(crate) fn decode_fragment(packet: &[u8]) -> Option<RemVSPFrameFragment> {
+    bincode::serde::decode_from_slice(packet, bincode::config::standard())
+        .ok()
+        .map(|(fragment, _)| fragment)
+}
@@ -0,0 +1,141 @@
+//! Selective retransmission: the receiver requests the fragments it is missing, which the sender
+//! retransmits from a short history of the frames sent within the latency budget.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{debug, warn};
+use remotia_core::common::network::remvsp::RemVSPNack;
+use tokio::{net::UdpSocket, task::JoinHandle};
+
+use super::MAX_DATAGRAM_SIZE;
+use crate::connection::ErrorBackoff;
+
+/// Maximum number of frames kept for retransmission, regardless of the latency budget
+const MAX_HISTORY_FRAMES: usize = 256;
+
+pub(crate) fn encode_nack(nack: &RemVSPNack) -> Vec<u8> {
+    bincode::serde::encode_to_vec(nack, bincode::config::standard()).unwrap()
+}
+
+pub(crate) fn decode_nack(packet: &[u8]) -> Option<RemVSPNack> {
+    bincode::serde::decode_from_slice(packet, bincode::config::standard())
+        .ok()
+        .map(|(nack, _)| nack)
+}
+
+struct SentFrame {
+    frame_id: u64,
+    sent_at: Instant,
+    packets: Vec<Vec<u8>>,
+}
+
+/// Encoded fragments of the frames sent within the latency budget
+#[derive(Clone)]
+pub(crate) struct SendHistory {
+    frames: Arc<Mutex<VecDeque<SentFrame>>>,
+    latency_budget: Duration,
+}
+
+impl SendHistory {
+    pub fn new(latency_budget: Duration) -> Self {
+        Self {
+            frames: Arc::new(Mutex::new(VecDeque::new())),
+            latency_budget,
+        }
+    }
+
+    pub fn push(&self, frame_id: u64, packets: Vec<Vec<u8>>) {
+        let now = Instant::now();
+        let mut frames = self.frames.lock().unwrap();
+
+        while frames.front().is_some_and(|frame| {
+            frames.len() >= MAX_HISTORY_FRAMES
+                || now.duration_since(frame.sent_at) >= self.latency_budget
+        }) {
+            frames.pop_front();
+        }
+
+        frames.push_back(SentFrame {
+            frame_id,
+            sent_at: now,
+            packets,
+        });
+    }
+
+    /// Packets to retransmit to satisfy a request, none if the frame is out of budget.
+    /// All the packets of the frame are retransmitted when the request lists no fragments.
+    pub fn requested_packets(&self, nack: &RemVSPNack) -> Vec<Vec<u8>> {
+        let frames = self.frames.lock().unwrap();
+
+        let frame = match frames.iter().find(|frame| frame.frame_id == nack.frame_id) {
+            Some(frame) if frame.sent_at.elapsed() < self.latency_budget => frame,
+            _ => return Vec::new(),
+        };
+
+        if nack.fragment_ids.is_empty() {
+            return frame.packets.clone();
+        }
+
+        nack.fragment_ids
+            .iter()
+            .filter_map(|fragment_id| frame.packets.get(*fragment_id as usize))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Serves the retransmission requests received on the sender socket
+pub(crate) fn spawn_nack_handler(socket: Arc<UdpSocket>, history: SendHistory) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut packet_buffer = vec![0; MAX_DATAGRAM_SIZE];
+        let mut errors = ErrorBackoff::default();
+
+        loop {
+            let packet_size = match socket.recv(&mut packet_buffer).await {
+                Ok(packet_size) => {
+                    errors.reset();
+                    packet_size
+                }
+                Err(error) => {
+                    debug!("Unable to receive retransmission request: {}", error);
+                    errors.wait().await;
+                    continue;
+                }
+            };
+
+            let nack = match decode_nack(&packet_buffer[..packet_size]) {
+                Some(nack) => nack,
+                None => {
+                    debug!("Invalid retransmission request of {} bytes", packet_size);
+                    continue;
+                }
+            };
+
+            let packets = history.requested_packets(&nack);
+            if nack.fragment_ids.is_empty() {
+                debug!(
+                    "Retransmitting frame {} ({} fragments)",
+                    nack.frame_id,
+                    packets.len()
+                );
+            } else {
+                debug!(
+                    "Retransmitting {}/{} fragments of frame {}",
+                    packets.len(),
+                    nack.fragment_ids.len(),
+                    nack.frame_id
+                );
+            }
+
+            for packet in packets {
+                if let Err(error) = socket.send(&packet).await {
+                    warn!("Unable to retransmit fragment: {}", error);
+                }
+            }
+        }
+    })
+}
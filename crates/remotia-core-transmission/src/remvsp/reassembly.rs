@@ -0,0 +1,406 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use remotia_core::{
+    common::network::remvsp::{RemVSPFrameFragment, RemVSPFrameHeader, RemVSPNack},
+    error::DropReason,
+};
+
+use super::fec::{fragment_length, group_range, parity, parity_fragments_count};
+
+/// Maximum number of frames tracked as entirely lost when a gap in the frame ids is detected
+const MAX_MISSING_FRAMES: u64 = 64;
+
+struct PartialFrame {
+    header: RemVSPFrameHeader,
+
+    /// Data fragments followed by the parity ones
+    fragments: Vec<Option<Vec<u8>>>,
+    received_data_fragments: usize,
+    recovered_fragments: usize,
+
+    first_arrival: Instant,
+
+    /// Time of the last received fragment or retransmission request
+    last_activity: Instant,
+}
+
+impl PartialFrame {
+    fn new(header: RemVSPFrameHeader, now: Instant) -> Self {
+        let fragments_count =
+            header.frame_fragments_count as usize + parity_fragments_count(&header);
+
+        Self {
+            header,
+            fragments: vec![None; fragments_count],
+            received_data_fragments: 0,
+            recovered_fragments: 0,
+            first_arrival: now,
+            last_activity: now,
+        }
+    }
+
+    fn data_fragments_count(&self) -> usize {
+        self.header.frame_fragments_count as usize
+    }
+
+    fn is_complete(&self) -> bool {
+        self.received_data_fragments == self.data_fragments_count()
+    }
+
+    /// Rebuilds the only missing data fragment of a group from its parity fragment, if possible
+    fn recover(&mut self, fragment_id: usize) {
+        let data_fragments_count = self.data_fragments_count();
+        let group = if fragment_id < data_fragments_count {
+            fragment_id / self.header.fec_group_size as usize
+        } else {
+            fragment_id - data_fragments_count
+        };
+
+        let parity_fragment = match &self.fragments[data_fragments_count + group] {
+            Some(parity_fragment) => parity_fragment,
+            None => return,
+        };
+
+        let range = group_range(&self.header, group);
+        let mut missing_fragments = range.clone().filter(|id| self.fragments[*id].is_none());
+
+        let missing_id = match (missing_fragments.next(), missing_fragments.next()) {
+            (Some(missing_id), None) => missing_id,
+            _ => return,
+        };
+
+        let present_fragments = range
+            .filter(|id| *id != missing_id)
+            .filter_map(|id| self.fragments[id].as_deref());
+
+        let mut recovered_fragment = parity(
+            present_fragments.chain(std::iter::once(&parity_fragment[..])),
+            self.header.fragment_size as usize,
+        );
+        recovered_fragment.truncate(fragment_length(&self.header, missing_id));
+
+        debug!(
+            "Recovered fragment {} of frame {}",
+            missing_id, self.header.frame_id
+        );
+
+        self.fragments[missing_id] = Some(recovered_fragment);
+        self.received_data_fragments += 1;
+        self.recovered_fragments += 1;
+    }
+}
+
+/// Frame none of whose fragments has been received, while fragments of newer frames have
+struct MissingFrame {
+    detection: Instant,
+
+    /// Time of the detection or of the last retransmission request
+    last_activity: Instant,
+}
+
+pub struct CompletedFrame {
+    pub header: RemVSPFrameHeader,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReceptionStats {
+    pub completed_frames: usize,
+
+    /// Completed frames which needed at least one fragment to be recovered
+    pub recovered_frames: usize,
+    pub recovered_fragments: usize,
+
+    /// Fragments requested for retransmission
+    pub requested_fragments: usize,
+
+    /// Frames none of whose fragments has been received, requested for retransmission as a whole
+    pub requested_frames: usize,
+
+    /// Frames dropped because incomplete or never received when expired or superseded by a
+    /// newer frame
+    pub lost_frames: usize,
+}
+
+/// Collects the fragments of the frames being received, in any order, recovering missing
+/// fragments through parity when FEC is enabled. Duplicated fragments and fragments of frames
+/// older than the last completed one are ignored.
+///
+/// Completed frames are returned in order. With a latency budget, frames never received are
+/// tracked as well, and the completed frames are held back while older ones may still be
+/// retransmitted within the budget.
+#[derive(Default)]
+pub struct FrameReassembler {
+    partial_frames: HashMap<u64, PartialFrame>,
+    missing_frames: HashMap<u64, MissingFrame>,
+    completed_frames: BTreeMap<u64, CompletedFrame>,
+    highest_frame_id: Option<u64>,
+    last_completed_frame: Option<u64>,
+    latency_budget: Option<Duration>,
+    stats: ReceptionStats,
+}
+
+impl FrameReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long incomplete and missing frames may be waited for through retransmissions
+    pub fn latency_budget(mut self, latency_budget: Duration) -> Self {
+        self.latency_budget = Some(latency_budget);
+        self
+    }
+
+    /// Number of frames waiting for fragments
+    pub fn pending_frames(&self) -> usize {
+        self.partial_frames.len() + self.missing_frames.len()
+    }
+
+    pub fn stats(&self) -> ReceptionStats {
+        self.stats
+    }
+
+    pub fn insert(
+        &mut self,
+        fragment: RemVSPFrameFragment,
+        now: Instant,
+    ) -> Result<Option<CompletedFrame>, DropReason> {
+        let header = fragment.frame_header;
+        validate(&fragment)?;
+
+        if self
+            .last_completed_frame
+            .is_some_and(|last_completed| header.frame_id <= last_completed)
+        {
+            debug!("Ignoring late fragment of frame {}", header.frame_id);
+            return Ok(None);
+        }
+
+        if self.completed_frames.contains_key(&header.frame_id) {
+            debug!(
+                "Ignoring fragment of already completed frame {}",
+                header.frame_id
+            );
+            return Ok(None);
+        }
+
+        self.track_missing_frames(header.frame_id, now);
+
+        let partial_frame = self
+            .partial_frames
+            .entry(header.frame_id)
+            .or_insert_with(|| PartialFrame::new(header, now));
+
+        if partial_frame.header.frame_fragments_count != header.frame_fragments_count
+            || partial_frame.header.fragment_size != header.fragment_size
+            || partial_frame.header.frame_length != header.frame_length
+            || partial_frame.header.fec_group_size != header.fec_group_size
+        {
+            return Err(DropReason::InvalidPacket);
+        }
+
+        let fragment_id = fragment.fragment_id as usize;
+        let slot = &mut partial_frame.fragments[fragment_id];
+        if slot.is_some() {
+            debug!(
+                "Ignoring duplicated fragment {} of frame {}",
+                fragment_id, header.frame_id
+            );
+            return Ok(None);
+        }
+
+        *slot = Some(fragment.data);
+        partial_frame.last_activity = now;
+        if fragment_id < partial_frame.data_fragments_count() {
+            partial_frame.received_data_fragments += 1;
+        }
+
+        if header.fec_group_size > 0 && !partial_frame.is_complete() {
+            partial_frame.recover(fragment_id);
+        }
+
+        if !partial_frame.is_complete() {
+            return Ok(None);
+        }
+
+        let mut partial_frame = self.partial_frames.remove(&header.frame_id).unwrap();
+        partial_frame
+            .fragments
+            .truncate(partial_frame.data_fragments_count());
+        let data = partial_frame
+            .fragments
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+
+        self.stats.completed_frames += 1;
+        if partial_frame.recovered_fragments > 0 {
+            self.stats.recovered_frames += 1;
+            self.stats.recovered_fragments += partial_frame.recovered_fragments;
+        }
+
+        self.completed_frames
+            .insert(header.frame_id, CompletedFrame { header, data });
+
+        Ok(self.next_frame(now))
+    }
+
+    /// Records the frames skipped by the ids received so far, if a latency budget is set
+    fn track_missing_frames(&mut self, frame_id: u64, now: Instant) {
+        self.missing_frames.remove(&frame_id);
+        if self.latency_budget.is_none() {
+            return;
+        }
+
+        let first_missing_frame = match self.highest_frame_id {
+            Some(highest_frame_id) if frame_id <= highest_frame_id => return,
+            Some(highest_frame_id) => {
+                (highest_frame_id + 1).max(frame_id.saturating_sub(MAX_MISSING_FRAMES))
+            }
+            None => frame_id,
+        };
+
+        for missing_frame_id in first_missing_frame..frame_id {
+            debug!("Frame {} is missing", missing_frame_id);
+            self.missing_frames.insert(
+                missing_frame_id,
+                MissingFrame {
+                    detection: now,
+                    last_activity: now,
+                },
+            );
+        }
+
+        self.highest_frame_id = Some(frame_id);
+    }
+
+    /// Returns the oldest completed frame, unless older frames may still be completed within
+    /// the latency budget. The older frames are dropped once it is returned.
+    pub fn next_frame(&mut self, now: Instant) -> Option<CompletedFrame> {
+        let frame_id = *self.completed_frames.keys().next()?;
+
+        if let Some(latency_budget) = self.latency_budget {
+            let waiting_partial_frames = self.partial_frames.iter().any(|(id, frame)| {
+                *id < frame_id && now.duration_since(frame.first_arrival) < latency_budget
+            });
+            let waiting_missing_frames = self.missing_frames.iter().any(|(id, frame)| {
+                *id < frame_id && now.duration_since(frame.detection) < latency_budget
+            });
+
+            if waiting_partial_frames || waiting_missing_frames {
+                return None;
+            }
+        }
+
+        let completed_frame = self.completed_frames.remove(&frame_id).unwrap();
+
+        let pending_frames = self.pending_frames();
+        self.partial_frames.retain(|id, _| *id > frame_id);
+        self.missing_frames.retain(|id, _| *id > frame_id);
+        self.stats.lost_frames += pending_frames - self.pending_frames();
+        self.last_completed_frame = Some(frame_id);
+
+        Some(completed_frame)
+    }
+
+    /// Lists the data fragments missing from the frames which received nothing for `delay`,
+    /// and the frames none of whose fragments has been received for `delay`, skipping the
+    /// frames older than the latency budget. Frames are listed at most once per `delay`, and
+    /// only if a latency budget is set.
+    pub fn missing_fragments(&mut self, now: Instant, delay: Duration) -> Vec<RemVSPNack> {
+        let mut nacks = Vec::new();
+        let latency_budget = match self.latency_budget {
+            Some(latency_budget) => latency_budget,
+            None => return nacks,
+        };
+
+        for partial_frame in self.partial_frames.values_mut() {
+            if now.duration_since(partial_frame.first_arrival) >= latency_budget
+                || now.duration_since(partial_frame.last_activity) < delay
+            {
+                continue;
+            }
+
+            let fragment_ids: Vec<u16> = partial_frame.fragments
+                [..partial_frame.data_fragments_count()]
+                .iter()
+                .enumerate()
+                .filter(|(_, fragment)| fragment.is_none())
+                .map(|(fragment_id, _)| fragment_id as u16)
+                .collect();
+
+            partial_frame.last_activity = now;
+            self.stats.requested_fragments += fragment_ids.len();
+
+            nacks.push(RemVSPNack {
+                frame_id: partial_frame.header.frame_id,
+                fragment_ids,
+            });
+        }
+
+        for (frame_id, missing_frame) in &mut self.missing_frames {
+            if now.duration_since(missing_frame.detection) >= latency_budget
+                || now.duration_since(missing_frame.last_activity) < delay
+            {
+                continue;
+            }
+
+            missing_frame.last_activity = now;
+            self.stats.requested_frames += 1;
+
+            nacks.push(RemVSPNack {
+                frame_id: *frame_id,
+                fragment_ids: Vec::new(),
+            });
+        }
+
+        nacks
+    }
+
+    /// Drops the frames whose first fragment arrived, or which were found missing, more than
+    /// `timeout` ago, returning how many have been dropped
+    pub fn expire(&mut self, now: Instant, timeout: Duration) -> usize {
+        let pending_frames = self.pending_frames();
+        self.partial_frames
+            .retain(|_, frame| now.duration_since(frame.first_arrival) < timeout);
+        self.missing_frames
+            .retain(|_, frame| now.duration_since(frame.detection) < timeout);
+
+        let expired_frames = pending_frames - self.pending_frames();
+        if expired_frames > 0 {
+            debug!("Dropped {} incomplete frames", expired_frames);
+            self.stats.lost_frames += expired_frames;
+        }
+
+        expired_frames
+    }
+}
+
+fn validate(fragment: &RemVSPFrameFragment) -> Result<(), DropReason> {
+    let header = &fragment.frame_header;
+    let fragment_id = fragment.fragment_id as usize;
+    let data_fragments_count = header.frame_fragments_count as usize;
+    let fragment_size = header.fragment_size as usize;
+
+    let valid_layout = data_fragments_count > 0
+        && fragment_size > 0
+        && (header.frame_length as usize)
+            .div_ceil(fragment_size)
+            .max(1)
+            == data_fragments_count;
+
+    let valid = valid_layout
+        && fragment_id < data_fragments_count + parity_fragments_count(header)
+        && fragment.data.len() == fragment_length(header, fragment_id);
+
+    if valid {
+        Ok(())
+    } else {
+        Err(DropReason::InvalidPacket)
+    }
+}
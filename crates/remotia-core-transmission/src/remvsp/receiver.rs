@@ -0,0 +1,239 @@
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowMutFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::net::UdpSocket;
+
+use super::{
+    decode_fragment,
+    nack::encode_nack,
+    reassembly::{FrameReassembler, ReceptionStats},
+    MAX_DATAGRAM_SIZE,
+};
+
+/// Receives frames sent by a `RemVSPFrameSender`, writing each completed frame to a buffer.
+///
+/// Packets which cannot be decoded or do not match the frame they belong to are skipped. If no
+/// frame is completed within the timeout, `DropReason::NoCompleteFrames` is reported, or
+/// `DropReason::UnrecoverableFrame` if incomplete frames older than the timeout were discarded.
+/// Fragments lost on FEC-protected frames are recovered when possible, and requested to the
+/// sender when NACK is enabled. Frames are written in order: with NACK, a completed frame is
+/// held back while older ones may still be retransmitted.
+pub struct RemVSPFrameReceiver<K, P> {
+    buffer_key: K,
+    timestamp_key: Option<P>,
+    frame_id_key: Option<P>,
+
+    socket: UdpSocket,
+    peer: Option<SocketAddr>,
+    timeout: Duration,
+    nack_delay: Option<Duration>,
+
+    reassembler: FrameReassembler,
+    stats: Arc<Mutex<ReceptionStats>>,
+    packet_buffer: Vec<u8>,
+}
+
+impl<K, P> RemVSPFrameReceiver<K, P> {
+    pub fn new(buffer_key: K, socket: UdpSocket) -> Self {
+        Self {
+            buffer_key,
+            timestamp_key: None,
+            frame_id_key: None,
+            socket,
+            peer: None,
+            timeout: Duration::from_millis(100),
+            nack_delay: None,
+            reassembler: FrameReassembler::new(),
+            stats: Arc::new(Mutex::new(ReceptionStats::default())),
+            packet_buffer: vec![0; MAX_DATAGRAM_SIZE],
+        }
+    }
+
+    /// Sets how long to wait for a frame to be completed (100 ms by default)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Requests the retransmission of the fragments missing from the frames which received
+    /// nothing for `delay`, and of the frames none of whose fragments arrived within `delay`
+    /// of a newer one, as long as they are within the latency budget
+    pub fn nack(mut self, delay: Duration, latency_budget: Duration) -> Self {
+        self.nack_delay = Some(delay);
+        self.reassembler = FrameReassembler::new().latency_budget(latency_budget);
+        self
+    }
+
+    /// Sets the key of the property the capture timestamp of the frame is written to
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.timestamp_key = Some(key);
+        self
+    }
+
+    /// Sets the key of the property the id of the frame is written to
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.frame_id_key = Some(key);
+        self
+    }
+
+    /// Shared handle to the counters of completed, recovered and lost frames
+    pub fn stats(&self) -> Arc<Mutex<ReceptionStats>> {
+        self.stats.clone()
+    }
+
+    fn update_stats(&self) {
+        *self.stats.lock().unwrap() = self.reassembler.stats();
+    }
+
+    async fn request_missing_fragments(&mut self, now: Instant) {
+        let (delay, peer) = match (self.nack_delay, self.peer) {
+            (Some(delay), Some(peer)) => (delay, peer),
+            _ => return,
+        };
+
+        let nacks = self.reassembler.missing_fragments(now, delay);
+
+        for nack in &nacks {
+            if nack.fragment_ids.is_empty() {
+                debug!("Requesting frame {}", nack.frame_id);
+            } else {
+                debug!(
+                    "Requesting {} fragments of frame {}",
+                    nack.fragment_ids.len(),
+                    nack.frame_id
+                );
+            }
+
+            if let Err(error) = self.socket.send_to(&encode_nack(nack), peer).await {
+                warn!("Unable to send retransmission request: {}", error);
+            }
+        }
+
+        if !nacks.is_empty() {
+            self.update_stats();
+        }
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for RemVSPFrameReceiver<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowMutFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let deadline = Instant::now() + self.timeout;
+        let mut expired_frames = 0;
+
+        let completed_frame = loop {
+            if let Some(completed_frame) = self.reassembler.next_frame(Instant::now()) {
+                self.update_stats();
+                break completed_frame;
+            }
+
+            let wake_up = match self.nack_delay {
+                Some(delay) => deadline.min(Instant::now() + delay),
+                None => deadline,
+            };
+
+            let received = tokio::time::timeout_at(
+                wake_up.into(),
+                self.socket.recv_from(&mut self.packet_buffer),
+            )
+            .await;
+
+            let now = Instant::now();
+            let expired = self.reassembler.expire(now, self.timeout);
+            if expired > 0 {
+                expired_frames += expired;
+                self.update_stats();
+            }
+
+            self.request_missing_fragments(now).await;
+
+            let packet_size = match received {
+                Ok(Ok((packet_size, peer))) => {
+                    self.peer = Some(peer);
+                    packet_size
+                }
+                Ok(Err(error)) => {
+                    warn!("Unable to receive packet: {}", error);
+                    frame_data.report_error(DropReason::ConnectionError);
+                    return Some(frame_data);
+                }
+                Err(_) if now >= deadline && expired_frames > 0 => {
+                    debug!("Dropped {} unrecoverable frames", expired_frames);
+                    frame_data.report_error(DropReason::UnrecoverableFrame);
+                    return Some(frame_data);
+                }
+                Err(_) if now >= deadline => {
+                    debug!(
+                        "No complete frames within {:?} ({} pending)",
+                        self.timeout,
+                        self.reassembler.pending_frames()
+                    );
+                    frame_data.report_error(DropReason::NoCompleteFrames);
+                    return Some(frame_data);
+                }
+                Err(_) => continue,
+            };
+
+            let fragment = match decode_fragment(&self.packet_buffer[..packet_size]) {
+                Some(fragment) => fragment,
+                None => {
+                    debug!("Skipping undecodable packet of {} bytes", packet_size);
+                    continue;
+                }
+            };
+
+            let frame_id = fragment.frame_header.frame_id;
+            let inserted = self.reassembler.insert(fragment, now);
+            self.update_stats();
+
+            match inserted {
+                Ok(Some(completed_frame)) => break completed_frame,
+                Ok(None) => continue,
+                Err(error) => {
+                    debug!("Skipping fragment of frame {}: {}", frame_id, error);
+                    continue;
+                }
+            }
+        };
+
+        let header = completed_frame.header;
+        debug!(
+            "Received frame {} ({} bytes)",
+            header.frame_id,
+            completed_frame.data.len()
+        );
+
+        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        buffer.clear();
+        buffer.extend_from_slice(&completed_frame.data);
+
+        if let Some(timestamp_key) = self.timestamp_key {
+            frame_data.set(timestamp_key, header.capture_timestamp);
+        }
+
+        if let Some(frame_id_key) = self.frame_id_key {
+            frame_data.set(frame_id_key, header.frame_id as u128);
+        }
+
+        Some(frame_data)
+    }
+}
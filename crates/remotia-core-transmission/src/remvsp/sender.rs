@@ -0,0 +1,238 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    common::network::remvsp::{RemVSPFrameFragment, RemVSPFrameHeader},
+    error::DropReason,
+    traits::{BorrowFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::{net::UdpSocket, task::JoinHandle};
+
+use crate::pacing::{Pacer, Pacing};
+
+use super::{
+    encode_fragment,
+    fec::{group_range, parity, parity_fragments_count},
+    nack::{spawn_nack_handler, SendHistory},
+    DEFAULT_MTU, FRAGMENT_OVERHEAD,
+};
+
+/// Splits the content of a buffer in fragments, sending each one as a datagram to the peer the
+/// socket is connected to. When FEC is enabled, a parity fragment is sent after each group of
+/// data fragments. When NACK is enabled, the fragments requested by the receiver are
+/// retransmitted as long as the frame is within the latency budget. When pacing is enabled, the
+/// fragments of each frame are spread over time instead of being sent in a single burst.
+///
+/// Frames needing more fragments than the header can count are reported as
+/// `DropReason::OversizedFrame`.
+pub struct RemVSPFrameSender<K, P> {
+    buffer_key: K,
+    timestamp_key: Option<P>,
+    frame_id_key: Option<P>,
+
+    socket: Arc<UdpSocket>,
+    fragment_size: usize,
+    fec_group_size: u16,
+
+    history: Option<SendHistory>,
+    nack_handler: Option<JoinHandle<()>>,
+
+    pacer: Option<Pacer>,
+    pacing_delay_key: Option<P>,
+
+    next_frame_id: u64,
+}
+
+impl<K, P> RemVSPFrameSender<K, P> {
+    pub fn new(buffer_key: K, socket: UdpSocket) -> Self {
+        Self {
+            buffer_key,
+            timestamp_key: None,
+            frame_id_key: None,
+            socket: Arc::new(socket),
+            fragment_size: DEFAULT_MTU - FRAGMENT_OVERHEAD,
+            fec_group_size: 0,
+            history: None,
+            nack_handler: None,
+            pacer: None,
+            pacing_delay_key: None,
+            next_frame_id: 0,
+        }
+    }
+
+    /// Sets the maximum size of the sent datagrams (1400 bytes by default)
+    pub fn mtu(mut self, mtu: usize) -> Self {
+        assert!(
+            mtu > FRAGMENT_OVERHEAD,
+            "MTU must be larger than {} bytes",
+            FRAGMENT_OVERHEAD
+        );
+        self.fragment_size = (mtu - FRAGMENT_OVERHEAD).min(u16::MAX as usize);
+        self
+    }
+
+    /// Adds a parity fragment for every `group_size` data fragments, allowing the receiver to
+    /// recover one lost fragment per group at the cost of `1 / group_size` redundancy
+    pub fn fec(mut self, group_size: u16) -> Self {
+        assert!(group_size > 0, "FEC group size must be positive");
+        self.fec_group_size = group_size;
+        self
+    }
+
+    /// Keeps the fragments sent within the latency budget, retransmitting them on request
+    pub fn nack(mut self, latency_budget: Duration) -> Self {
+        self.history = Some(SendHistory::new(latency_budget));
+        self
+    }
+
+    /// Spreads the fragments of each frame according to the pacing
+    pub fn pacing(mut self, pacing: Pacing) -> Self {
+        self.pacer = Some(Pacer::new(pacing));
+        self
+    }
+
+    /// Sets the key of the property the time each frame has been held back by the pacing is
+    /// written to, in microseconds
+    pub fn pacing_delay_key(mut self, key: P) -> Self {
+        self.pacing_delay_key = Some(key);
+        self
+    }
+
+    /// Sets the key of the property whose value is sent as the capture timestamp
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.timestamp_key = Some(key);
+        self
+    }
+
+    /// Sets the key of the property the id assigned to each frame is written to
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.frame_id_key = Some(key);
+        self
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for RemVSPFrameSender<K, P>
+where
+    K: Send,
+    P: Copy + Send,
+    F: BorrowFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        if let (Some(history), None) = (&self.history, &self.nack_handler) {
+            self.nack_handler = Some(spawn_nack_handler(self.socket.clone(), history.clone()));
+        }
+
+        let frame_id = self.next_frame_id;
+        self.next_frame_id += 1;
+
+        if let Some(frame_id_key) = self.frame_id_key {
+            frame_data.set(frame_id_key, frame_id as u128);
+        }
+
+        let capture_timestamp = self
+            .timestamp_key
+            .and_then(|key| frame_data.get(&key))
+            .unwrap_or(0);
+
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+        let fragments_count = buffer.len().div_ceil(self.fragment_size).max(1);
+
+        let frame_header = RemVSPFrameHeader {
+            frame_id,
+            frame_fragments_count: fragments_count.min(u16::MAX as usize) as u16,
+            fragment_size: self.fragment_size as u16,
+            frame_length: buffer.len() as u32,
+            fec_group_size: self.fec_group_size,
+            capture_timestamp,
+        };
+        let parity_fragments_count = parity_fragments_count(&frame_header);
+
+        if fragments_count + parity_fragments_count > u16::MAX as usize {
+            warn!(
+                "Unable to send frame of {} bytes, too many fragments",
+                buffer.len()
+            );
+            frame_data.report_error(DropReason::OversizedFrame);
+            return Some(frame_data);
+        }
+
+        debug!(
+            "Sending frame {} ({} bytes, {} fragments, {} parity fragments)",
+            frame_id,
+            buffer.len(),
+            fragments_count,
+            parity_fragments_count
+        );
+
+        let data_fragments: Vec<&[u8]> = buffer.chunks(self.fragment_size).collect();
+        let data_fragments = if data_fragments.is_empty() {
+            vec![&buffer[..]]
+        } else {
+            data_fragments
+        };
+
+        let parity_fragments: Vec<Vec<u8>> = (0..parity_fragments_count)
+            .map(|group| {
+                parity(
+                    data_fragments[group_range(&frame_header, group)]
+                        .iter()
+                        .copied(),
+                    self.fragment_size,
+                )
+            })
+            .collect();
+
+        let packets: Vec<Vec<u8>> = data_fragments
+            .into_iter()
+            .map(|data| data.to_vec())
+            .chain(parity_fragments)
+            .enumerate()
+            .map(|(fragment_id, data)| {
+                encode_fragment(&RemVSPFrameFragment {
+                    frame_header,
+                    fragment_id: fragment_id as u16,
+                    data,
+                })
+            })
+            .collect();
+
+        if let Some(pacer) = &mut self.pacer {
+            pacer.start_frame(packets.iter().map(|packet| packet.len()).sum());
+        }
+
+        for packet in &packets {
+            if let Some(pacer) = &mut self.pacer {
+                pacer.pace(packet.len()).await;
+            }
+
+            if let Err(error) = self.socket.send(packet).await {
+                warn!("Unable to send fragment: {}", error);
+            }
+        }
+
+        if let (Some(pacer), Some(pacing_delay_key)) = (&self.pacer, self.pacing_delay_key) {
+            frame_data.set(pacing_delay_key, pacer.frame_delay().as_micros());
+        }
+
+        if let Some(history) = &self.history {
+            history.push(frame_id, packets);
+        }
+
+        Some(frame_data)
+    }
+}
+
+impl<K, P> Drop for RemVSPFrameSender<K, P> {
+    fn drop(&mut self) {
+        if let Some(nack_handler) = &self.nack_handler {
+            nack_handler.abort();
+        }
+    }
+}
@@ -1,30 +1,180 @@
 use async_trait::async_trait;
 
+use log::warn;
 use remotia_buffer_utils::BytesMut;
-use remotia_core::traits::{BorrowFrameProperties, FrameProcessor};
-use tokio::io::AsyncWriteExt;
+use remotia_core::error::DropReason;
+use remotia_core::traits::{
+    BorrowFrameProperties, FrameError, FrameProcessor, OptionalPropertyKey,
+};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
+use tokio::sync::watch;
 
-pub struct TcpFrameSender<K> {
+use crate::connection::{ConnectionManager, ConnectionState};
+use crate::framing::{FrameHeader, HeaderKeys};
+use crate::pacing::{Pacer, Pacing};
+
+/// Size of the writes of a paced frame
+const PACING_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Sends the content of a buffer, preceded by a `FrameHeader` carrying its length
+/// and optionally the frame id, timestamp and flags read from the frame properties whose keys
+/// are set.
+///
+/// Frames which cannot be sent because the connection is down are reported as
+/// `DropReason::ConnectionError`.
+pub struct TcpFrameSender<K, P = (), S = TcpStream> {
     buffer_key: K,
-    socket: TcpStream,
+    header_keys: HeaderKeys<P>,
+    connection: ConnectionManager<S>,
+
+    pacer: Option<Pacer>,
+    pacing_delay_key: P,
+}
+
+impl<K, S: Send + 'static> TcpFrameSender<K, (), S> {
+    pub fn new(buffer_key: K, socket: S) -> Self {
+        Self::with_connection(buffer_key, ConnectionManager::from_stream(socket))
+    }
+
+    /// Sends the frames over a connection which is re-established whenever lost
+    pub fn with_connection(buffer_key: K, connection: ConnectionManager<S>) -> Self {
+        Self {
+            buffer_key,
+            header_keys: HeaderKeys::new(),
+            connection,
+            pacer: None,
+            pacing_delay_key: (),
+        }
+    }
+
+    pub fn frame_id_key<P>(self, key: P) -> TcpFrameSender<K, Option<P>, S> {
+        self.typed().frame_id_key(key)
+    }
+
+    pub fn timestamp_key<P>(self, key: P) -> TcpFrameSender<K, Option<P>, S> {
+        self.typed().timestamp_key(key)
+    }
+
+    pub fn flags_key<P>(self, key: P) -> TcpFrameSender<K, Option<P>, S> {
+        self.typed().flags_key(key)
+    }
+
+    /// Sets the key of the property the time each frame has been held back by the pacing is
+    /// written to, in microseconds
+    pub fn pacing_delay_key<P>(self, key: P) -> TcpFrameSender<K, Option<P>, S> {
+        self.typed().pacing_delay_key(key)
+    }
+
+    fn typed<P>(self) -> TcpFrameSender<K, Option<P>, S> {
+        TcpFrameSender {
+            buffer_key: self.buffer_key,
+            header_keys: self.header_keys.typed(),
+            connection: self.connection,
+            pacer: self.pacer,
+            pacing_delay_key: None,
+        }
+    }
 }
 
-impl<K> TcpFrameSender<K> {
-    pub fn new(buffer_key: K, socket: TcpStream) -> Self {
-        Self { buffer_key, socket }
+impl<K, P, S> TcpFrameSender<K, Option<P>, S> {
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
+
+    /// Sets the key of the property the time each frame has been held back by the pacing is
+    /// written to, in microseconds
+    pub fn pacing_delay_key(mut self, key: P) -> Self {
+        self.pacing_delay_key = Some(key);
+        self
+    }
+}
+
+impl<K, P, S: Send + 'static> TcpFrameSender<K, P, S> {
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection.state()
+    }
+
+    /// Writes the frames in chunks spread according to the pacing instead of in a single burst
+    pub fn pacing(mut self, pacing: Pacing) -> Self {
+        self.pacer = Some(Pacer::new(pacing));
+        self
     }
 }
 
 #[async_trait]
-impl<F, K> FrameProcessor<F> for TcpFrameSender<K>
+impl<F, K, P, S> FrameProcessor<F> for TcpFrameSender<K, P, S>
 where
     K: Send,
-    F: BorrowFrameProperties<K, BytesMut> + Send + 'static,
+    P: OptionalPropertyKey<F, u128>,
+    S: AsyncWrite + Unpin + Send + 'static,
+    F: BorrowFrameProperties<K, BytesMut> + FrameError<DropReason> + Send + 'static,
 {
-    async fn process(&mut self, frame_data: F) -> Option<F> {
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
         let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
-        self.socket.write_all(buffer).await.unwrap();
+
+        let mut header = match FrameHeader::new(buffer.len()) {
+            Ok(header) => header,
+            Err(_) => {
+                warn!("Frame of {} bytes is too large to be sent", buffer.len());
+                frame_data.report_error(DropReason::OversizedFrame);
+                return Some(frame_data);
+            }
+        };
+        self.header_keys.fill(&mut header, &frame_data);
+
+        let socket = match self.connection.stream().await {
+            Some(socket) => socket,
+            None => {
+                frame_data.report_error(DropReason::ConnectionError);
+                return Some(frame_data);
+            }
+        };
+
+        let mut sent = Ok(());
+        match &mut self.pacer {
+            Some(pacer) => {
+                pacer.start_frame(FrameHeader::SIZE + buffer.len());
+
+                let header = header.encode();
+                let chunks = std::iter::once(&header[..]).chain(buffer.chunks(PACING_CHUNK_SIZE));
+                for chunk in chunks {
+                    pacer.pace(chunk.len()).await;
+                    sent = socket.write_all(chunk).await;
+                    if sent.is_err() {
+                        break;
+                    }
+                }
+            }
+            None => {
+                sent = socket.write_all(&header.encode()).await;
+                if sent.is_ok() {
+                    sent = socket.write_all(buffer).await;
+                }
+            }
+        }
+
+        if let Err(error) = sent {
+            self.connection.disconnect(&error);
+            frame_data.report_error(DropReason::ConnectionError);
+        }
+
+        if let Some(pacer) = &self.pacer {
+            self.pacing_delay_key
+                .set_property(&mut frame_data, pacer.frame_delay().as_micros());
+        }
+
         Some(frame_data)
     }
 }
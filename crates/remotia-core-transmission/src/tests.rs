@@ -0,0 +1,1770 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    common::network::remvsp::{RemVSPFrameFragment, RemVSPFrameHeader},
+    error::DropReason,
+    traits::{
+        BorrowFrameProperties, BorrowMutFrameProperties, FrameError, FrameProcessor,
+        FrameProperties,
+    },
+};
+use tokio::{
+    net::{TcpListener, TcpStream, UdpSocket},
+    task::JoinHandle,
+};
+
+use crate::{
+    broadcast::{ClientEvent, LeaveReason, SlowClientPolicy, TcpBroadcastSender},
+    clock_sync::{ClockSample, ClockSyncServer, ClockSynchronizer, OffsetEstimator},
+    congestion::{
+        controller::BitrateController,
+        decode_feedback,
+        estimator::{BandwidthEstimator, BandwidthUsage, FrameResult},
+        reporter::FeedbackReporter,
+    },
+    connection::{Backoff, ConnectionManager, ConnectionState, TcpClient, TcpServer},
+    framing::FrameHeader,
+    impairment::{
+        processor::ImpairmentEmulator, proxy::UdpImpairmentProxy, trace::ImpairmentTrace,
+        ImpairmentProfile, LossModel, NetworkEmulator,
+    },
+    jitter::JitterBuffer,
+    loopback::{link, LoopbackFrameReceiver, LoopbackFrameSender},
+    pacing::Pacing,
+    receiver::TcpFrameReceiver,
+    remvsp::{
+        decode_fragment,
+        fec::parity,
+        reassembly::{FrameReassembler, ReceptionStats},
+        receiver::RemVSPFrameReceiver,
+        sender::RemVSPFrameSender,
+        FRAGMENT_OVERHEAD,
+    },
+    sender::TcpFrameSender,
+};
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+struct FrameBuffer;
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+enum Stat {
+    FrameId,
+    Timestamp,
+    Flags,
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+enum Buffer {
+    Primary,
+    Secondary,
+}
+
+#[derive(Default, Clone)]
+struct TestFrameData {
+    buffer: BytesMut,
+    secondary_buffer: BytesMut,
+    stats: HashMap<Stat, u128>,
+    error: Option<DropReason>,
+}
+
+impl TestFrameData {
+    fn with_buffer(content: &[u8]) -> Self {
+        Self {
+            buffer: BytesMut::from(content),
+            ..Default::default()
+        }
+    }
+}
+
+impl BorrowFrameProperties<FrameBuffer, BytesMut> for TestFrameData {
+    fn get_ref(&self, _: &FrameBuffer) -> Option<&BytesMut> {
+        Some(&self.buffer)
+    }
+}
+
+impl BorrowMutFrameProperties<FrameBuffer, BytesMut> for TestFrameData {
+    fn get_mut_ref(&mut self, _: &FrameBuffer) -> Option<&mut BytesMut> {
+        Some(&mut self.buffer)
+    }
+}
+
+impl BorrowFrameProperties<Buffer, BytesMut> for TestFrameData {
+    fn get_ref(&self, key: &Buffer) -> Option<&BytesMut> {
+        match key {
+            Buffer::Primary => Some(&self.buffer),
+            Buffer::Secondary => Some(&self.secondary_buffer),
+        }
+    }
+}
+
+impl BorrowMutFrameProperties<Buffer, BytesMut> for TestFrameData {
+    fn get_mut_ref(&mut self, key: &Buffer) -> Option<&mut BytesMut> {
+        match key {
+            Buffer::Primary => Some(&mut self.buffer),
+            Buffer::Secondary => Some(&mut self.secondary_buffer),
+        }
+    }
+}
+
+impl FrameProperties<Stat, u128> for TestFrameData {
+    fn set(&mut self, key: Stat, value: u128) {
+        self.stats.insert(key, value);
+    }
+
+    fn get(&self, key: &Stat) -> Option<u128> {
+        self.stats.get(key).copied()
+    }
+}
+
+impl FrameError<DropReason> for TestFrameData {
+    fn report_error(&mut self, error: DropReason) {
+        self.error = Some(error);
+    }
+
+    fn get_error(&self) -> Option<DropReason> {
+        self.error
+    }
+}
+
+async fn tcp_pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (server, _) = listener.accept().await.unwrap();
+    (client, server)
+}
+
+#[test]
+fn test_frame_header_roundtrip() {
+    let header = FrameHeader {
+        length: 1234,
+        flags: 0b101,
+        frame_id: 42,
+        timestamp: 1_700_000_000_000,
+    };
+
+    assert_eq!(FrameHeader::decode(&header.encode()), header);
+
+    assert_eq!(
+        FrameHeader::new(u32::MAX as usize).unwrap().length,
+        u32::MAX
+    );
+    assert!(FrameHeader::new(u32::MAX as usize + 1).is_err());
+}
+
+#[tokio::test]
+async fn test_tcp_variable_length_frames() {
+    let (client, server) = tcp_pair().await;
+
+    let mut sender = TcpFrameSender::new(FrameBuffer, client)
+        .frame_id_key(Stat::FrameId)
+        .timestamp_key(Stat::Timestamp)
+        .flags_key(Stat::Flags);
+    let mut receiver = TcpFrameReceiver::new(FrameBuffer, server)
+        .frame_id_key(Stat::FrameId)
+        .timestamp_key(Stat::Timestamp)
+        .max_frame_size(8);
+
+    let payloads: [&[u8]; 4] = [b"short", b"", b"too long frame", b"12345678"];
+    for (frame_id, payload) in payloads.iter().enumerate() {
+        let mut frame_data = TestFrameData::with_buffer(payload);
+        frame_data.set(Stat::FrameId, frame_id as u128);
+        frame_data.set(Stat::Timestamp, 1000 + frame_id as u128);
+        frame_data.set(Stat::Flags, 1);
+        sender.process(frame_data).await.unwrap();
+    }
+
+    for (frame_id, payload) in payloads.iter().enumerate() {
+        let frame_data = receiver
+            .process(TestFrameData::with_buffer(b"previous content"))
+            .await
+            .unwrap();
+
+        if payload.len() > 8 {
+            assert_eq!(
+                frame_data.get_error(),
+                Some(DropReason::InvalidWholeFrameHeader)
+            );
+            continue;
+        }
+
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], *payload);
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(frame_id as u128));
+        assert_eq!(
+            frame_data.get(&Stat::Timestamp),
+            Some(1000 + frame_id as u128)
+        );
+        assert_eq!(frame_data.get(&Stat::Flags), None);
+    }
+}
+
+/// Frame without any property besides its buffer and error
+#[derive(Default)]
+struct PlainFrameData {
+    buffer: BytesMut,
+    error: Option<DropReason>,
+}
+
+impl BorrowFrameProperties<FrameBuffer, BytesMut> for PlainFrameData {
+    fn get_ref(&self, _: &FrameBuffer) -> Option<&BytesMut> {
+        Some(&self.buffer)
+    }
+}
+
+impl BorrowMutFrameProperties<FrameBuffer, BytesMut> for PlainFrameData {
+    fn get_mut_ref(&mut self, _: &FrameBuffer) -> Option<&mut BytesMut> {
+        Some(&mut self.buffer)
+    }
+}
+
+impl FrameError<DropReason> for PlainFrameData {
+    fn report_error(&mut self, error: DropReason) {
+        self.error = Some(error);
+    }
+
+    fn get_error(&self) -> Option<DropReason> {
+        self.error
+    }
+}
+
+#[tokio::test]
+async fn test_tcp_frames_without_properties() {
+    let (client, server) = tcp_pair().await;
+
+    // The header keys are only required once set, and paced frames span several chunks
+    let mut sender = TcpFrameSender::new(FrameBuffer, client).pacing(Pacing::Rate(1_000_000_000));
+    let mut receiver = TcpFrameReceiver::new(FrameBuffer, server);
+
+    let payload: Vec<u8> = (0..40_000).map(|i| i as u8).collect();
+    let frame_data = PlainFrameData {
+        buffer: BytesMut::from(&payload[..]),
+        ..Default::default()
+    };
+    let frame_data = sender.process(frame_data).await.unwrap();
+    assert_eq!(frame_data.error, None);
+
+    let frame_data = receiver.process(PlainFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.error, None);
+    assert_eq!(&frame_data.buffer[..], &payload[..]);
+}
+
+/// Fragment of a 5 bytes frame split in 3 fragments
+fn fragment(frame_id: u64, fragment_id: u16, data: &[u8]) -> RemVSPFrameFragment {
+    RemVSPFrameFragment {
+        frame_header: RemVSPFrameHeader {
+            frame_id,
+            frame_fragments_count: 3,
+            fragment_size: 2,
+            frame_length: 5,
+            fec_group_size: 0,
+            capture_timestamp: 0,
+        },
+        fragment_id,
+        data: data.to_vec(),
+    }
+}
+
+fn fec_fragment(frame_id: u64, fragment_id: u16, data: &[u8]) -> RemVSPFrameFragment {
+    let mut fragment = fragment(frame_id, fragment_id, data);
+    fragment.frame_header.fec_group_size = 2;
+    fragment
+}
+
+#[test]
+fn test_remvsp_reassembly() {
+    let mut reassembler = FrameReassembler::new();
+    let now = Instant::now();
+
+    // Out of order and duplicated fragments
+    assert!(reassembler
+        .insert(fragment(0, 2, b"e"), now)
+        .unwrap()
+        .is_none());
+    assert!(reassembler
+        .insert(fragment(0, 0, b"ab"), now)
+        .unwrap()
+        .is_none());
+    assert!(reassembler
+        .insert(fragment(0, 0, b"ab"), now)
+        .unwrap()
+        .is_none());
+    let completed_frame = reassembler
+        .insert(fragment(0, 1, b"cd"), now)
+        .unwrap()
+        .unwrap();
+    assert_eq!(completed_frame.data, b"abcde");
+
+    // Late fragments of completed frames are ignored
+    assert!(reassembler
+        .insert(fragment(0, 1, b"cd"), now)
+        .unwrap()
+        .is_none());
+    assert_eq!(reassembler.pending_frames(), 0);
+
+    assert_eq!(
+        reassembler.insert(fragment(1, 3, b"xy"), now).err(),
+        Some(DropReason::InvalidPacket)
+    );
+    assert_eq!(
+        reassembler.insert(fragment(1, 0, b"x"), now).err(),
+        Some(DropReason::InvalidPacket)
+    );
+
+    assert!(reassembler
+        .insert(fragment(1, 0, b"xy"), now)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        reassembler.expire(now + Duration::from_millis(50), Duration::from_millis(100)),
+        0
+    );
+    assert_eq!(
+        reassembler.expire(now + Duration::from_millis(150), Duration::from_millis(100)),
+        1
+    );
+}
+
+#[test]
+fn test_remvsp_fec_recovery() {
+    let mut reassembler = FrameReassembler::new();
+    let now = Instant::now();
+
+    // Groups are [0, 1] and [2], with parity fragments 3 and 4
+    let first_parity = parity([&b"ab"[..], &b"cd"[..]].into_iter(), 2);
+    let second_parity = parity([&b"e"[..]].into_iter(), 2);
+
+    // Last fragment of the frame recovered from its parity
+    assert!(reassembler
+        .insert(fec_fragment(0, 0, b"ab"), now)
+        .unwrap()
+        .is_none());
+    assert!(reassembler
+        .insert(fec_fragment(0, 1, b"cd"), now)
+        .unwrap()
+        .is_none());
+    let completed_frame = reassembler
+        .insert(fec_fragment(0, 4, &second_parity), now)
+        .unwrap()
+        .unwrap();
+    assert_eq!(completed_frame.data, b"abcde");
+
+    // Parity received before the fragments of its group
+    assert!(reassembler
+        .insert(fec_fragment(1, 3, &first_parity), now)
+        .unwrap()
+        .is_none());
+    assert!(reassembler
+        .insert(fec_fragment(1, 1, b"cd"), now)
+        .unwrap()
+        .is_none());
+    let completed_frame = reassembler
+        .insert(fec_fragment(1, 2, b"e"), now)
+        .unwrap()
+        .unwrap();
+    assert_eq!(completed_frame.data, b"abcde");
+
+    // Two fragments lost in the same group cannot be recovered
+    assert!(reassembler
+        .insert(fec_fragment(2, 2, b"e"), now)
+        .unwrap()
+        .is_none());
+    assert!(reassembler
+        .insert(fec_fragment(2, 3, &first_parity), now)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        reassembler.expire(now + Duration::from_secs(1), Duration::from_millis(100)),
+        1
+    );
+
+    assert_eq!(
+        reassembler.stats(),
+        ReceptionStats {
+            completed_frames: 2,
+            recovered_frames: 2,
+            recovered_fragments: 2,
+            requested_fragments: 0,
+            requested_frames: 0,
+            lost_frames: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_remvsp_udp_transmission() {
+    let receiver_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sender_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    sender_socket
+        .connect(receiver_socket.local_addr().unwrap())
+        .await
+        .unwrap();
+
+    let mut sender = RemVSPFrameSender::new(FrameBuffer, sender_socket)
+        .mtu(100)
+        .fec(4)
+        .timestamp_key(Stat::Timestamp);
+    let mut receiver = RemVSPFrameReceiver::new(FrameBuffer, receiver_socket)
+        .timeout(Duration::from_millis(200))
+        .timestamp_key(Stat::Timestamp)
+        .frame_id_key(Stat::FrameId);
+    let stats = receiver.stats();
+
+    let payload: Vec<u8> = (0..1000).map(|value| (value % 251) as u8).collect();
+    let mut frame_data = TestFrameData::with_buffer(&payload);
+    frame_data.set(Stat::Timestamp, 1234);
+    sender.process(frame_data).await.unwrap();
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(&frame_data.buffer[..], &payload[..]);
+    assert_eq!(frame_data.get(&Stat::Timestamp), Some(1234));
+    assert_eq!(frame_data.get(&Stat::FrameId), Some(0));
+    assert_eq!(stats.lock().unwrap().completed_frames, 1);
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::NoCompleteFrames));
+}
+
+#[tokio::test]
+async fn test_remvsp_invalid_packets() {
+    let receiver_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sender_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    sender_socket
+        .connect(receiver_socket.local_addr().unwrap())
+        .await
+        .unwrap();
+
+    // Undecodable datagrams are skipped while waiting for the frame
+    sender_socket.send(b"not a fragment").await.unwrap();
+
+    let mut sender = RemVSPFrameSender::new(FrameBuffer, sender_socket).mtu(FRAGMENT_OVERHEAD + 1);
+    let mut receiver = RemVSPFrameReceiver::new(FrameBuffer, receiver_socket)
+        .timeout(Duration::from_millis(200))
+        .frame_id_key(Stat::FrameId);
+
+    let frame_data = sender
+        .process(TestFrameData::with_buffer(b"abc"))
+        .await
+        .unwrap();
+    assert_eq!(frame_data.get_error(), None);
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(&frame_data.buffer[..], b"abc");
+
+    // Frames needing more fragments than the header can count are not sent
+    let frame_data = sender
+        .process(TestFrameData::with_buffer(&vec![0; u16::MAX as usize + 1]))
+        .await
+        .unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::OversizedFrame));
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::NoCompleteFrames));
+}
+
+#[test]
+fn test_remvsp_missing_fragments() {
+    let now = Instant::now();
+    let delay = Duration::from_millis(10);
+    let latency_budget = Duration::from_millis(100);
+    let mut reassembler = FrameReassembler::new().latency_budget(latency_budget);
+
+    assert!(reassembler
+        .insert(fragment(0, 1, b"cd"), now)
+        .unwrap()
+        .is_none());
+
+    // Nothing is requested before the delay elapses
+    assert!(reassembler
+        .missing_fragments(now + Duration::from_millis(5), delay)
+        .is_empty());
+
+    let nacks = reassembler.missing_fragments(now + delay, delay);
+    assert_eq!(nacks.len(), 1);
+    assert_eq!(nacks[0].frame_id, 0);
+    assert_eq!(nacks[0].fragment_ids, vec![0, 2]);
+
+    // Requests are repeated at most once per delay
+    assert!(reassembler
+        .missing_fragments(now + Duration::from_millis(15), delay)
+        .is_empty());
+
+    // Frames out of budget are given up
+    assert!(reassembler
+        .missing_fragments(now + latency_budget, delay)
+        .is_empty());
+
+    assert_eq!(reassembler.stats().requested_fragments, 2);
+}
+
+#[test]
+fn test_remvsp_missing_frames() {
+    let now = Instant::now();
+    let delay = Duration::from_millis(10);
+    let latency_budget = Duration::from_millis(100);
+    let mut reassembler = FrameReassembler::new().latency_budget(latency_budget);
+    let fragments: [&[u8]; 3] = [b"ab", b"cd", b"e"];
+
+    for (fragment_id, data) in fragments.into_iter().enumerate() {
+        reassembler
+            .insert(fragment(0, fragment_id as u16, data), now)
+            .unwrap();
+        assert!(reassembler
+            .insert(fragment(3, fragment_id as u16, data), now)
+            .unwrap()
+            .is_none());
+    }
+
+    // Frame 3 is held back while the skipped frames are requested as a whole
+    let mut nacks = reassembler.missing_fragments(now + delay, delay);
+    nacks.sort_by_key(|nack| nack.frame_id);
+    assert_eq!(nacks.len(), 2);
+    assert_eq!((nacks[0].frame_id, nacks[1].frame_id), (1, 2));
+    assert!(nacks.iter().all(|nack| nack.fragment_ids.is_empty()));
+
+    for (fragment_id, data) in fragments.into_iter().enumerate() {
+        reassembler
+            .insert(fragment(2, fragment_id as u16, data), now + delay)
+            .unwrap();
+    }
+
+    // Frame 2 is retransmitted, frame 1 is given up once out of budget
+    assert!(reassembler.next_frame(now + delay).is_none());
+    let completed_frame = reassembler.next_frame(now + latency_budget).unwrap();
+    assert_eq!(completed_frame.header.frame_id, 2);
+    let completed_frame = reassembler.next_frame(now + latency_budget).unwrap();
+    assert_eq!(completed_frame.header.frame_id, 3);
+    assert!(reassembler.next_frame(now + latency_budget).is_none());
+
+    let stats = reassembler.stats();
+    assert_eq!(stats.requested_frames, 2);
+    assert_eq!(stats.completed_frames, 3);
+    assert_eq!(stats.lost_frames, 1);
+}
+
+/// Relays the packets between a RemVSP sender and receiver, losing the first transmission of
+/// the fragments matching `lose`
+fn spawn_lossy_proxy(
+    proxy_socket: UdpSocket,
+    sender_address: SocketAddr,
+    receiver_address: SocketAddr,
+    lose: impl Fn(&RemVSPFrameFragment) -> bool + Send + 'static,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut packet_buffer = vec![0; 2048];
+        let mut lost_fragments = HashSet::new();
+
+        loop {
+            let (packet_size, source) = proxy_socket.recv_from(&mut packet_buffer).await.unwrap();
+            let packet = &packet_buffer[..packet_size];
+
+            if source == sender_address {
+                let fragment = decode_fragment(packet).unwrap();
+                let fragment_key = (fragment.frame_header.frame_id, fragment.fragment_id);
+                if lose(&fragment) && lost_fragments.insert(fragment_key) {
+                    continue;
+                }
+
+                proxy_socket
+                    .send_to(packet, receiver_address)
+                    .await
+                    .unwrap();
+            } else {
+                proxy_socket.send_to(packet, sender_address).await.unwrap();
+            }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_remvsp_nack_retransmission() {
+    let receiver_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sender_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let proxy_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let receiver_address = receiver_socket.local_addr().unwrap();
+    let sender_address = sender_socket.local_addr().unwrap();
+    sender_socket
+        .connect(proxy_socket.local_addr().unwrap())
+        .await
+        .unwrap();
+
+    let proxy = spawn_lossy_proxy(proxy_socket, sender_address, receiver_address, |fragment| {
+        fragment.fragment_id == 1
+    });
+
+    let mut sender = RemVSPFrameSender::new(FrameBuffer, sender_socket)
+        .mtu(100)
+        .nack(Duration::from_secs(1));
+    let mut receiver = RemVSPFrameReceiver::new(FrameBuffer, receiver_socket)
+        .timeout(Duration::from_millis(500))
+        .nack(Duration::from_millis(20), Duration::from_secs(1));
+    let stats = receiver.stats();
+
+    let payload: Vec<u8> = (0..1000).map(|value| (value % 251) as u8).collect();
+    sender
+        .process(TestFrameData::with_buffer(&payload))
+        .await
+        .unwrap();
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(&frame_data.buffer[..], &payload[..]);
+
+    let stats = *stats.lock().unwrap();
+    assert_eq!(stats.completed_frames, 1);
+    assert_eq!(stats.requested_fragments, 1);
+
+    proxy.abort();
+}
+
+#[tokio::test]
+async fn test_remvsp_frame_retransmission() {
+    let receiver_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sender_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let proxy_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let receiver_address = receiver_socket.local_addr().unwrap();
+    let sender_address = sender_socket.local_addr().unwrap();
+    sender_socket
+        .connect(proxy_socket.local_addr().unwrap())
+        .await
+        .unwrap();
+
+    // The only fragment of frame 1 is lost
+    let proxy = spawn_lossy_proxy(proxy_socket, sender_address, receiver_address, |fragment| {
+        fragment.frame_header.frame_id == 1
+    });
+
+    let mut sender = RemVSPFrameSender::new(FrameBuffer, sender_socket)
+        .mtu(100)
+        .nack(Duration::from_secs(1))
+        .frame_id_key(Stat::FrameId);
+    let mut receiver = RemVSPFrameReceiver::new(FrameBuffer, receiver_socket)
+        .timeout(Duration::from_millis(500))
+        .nack(Duration::from_millis(20), Duration::from_secs(1))
+        .frame_id_key(Stat::FrameId);
+    let stats = receiver.stats();
+
+    for frame_id in 0..3u8 {
+        sender
+            .process(TestFrameData::with_buffer(&[frame_id; 10]))
+            .await
+            .unwrap();
+    }
+
+    for frame_id in 0..3u8 {
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(frame_id as u128));
+        assert_eq!(&frame_data.buffer[..], &[frame_id; 10]);
+    }
+
+    let stats = *stats.lock().unwrap();
+    assert_eq!(stats.completed_frames, 3);
+    assert_eq!(stats.requested_frames, 1);
+    assert_eq!(stats.lost_frames, 0);
+
+    proxy.abort();
+}
+
+#[test]
+fn test_network_emulator() {
+    let profile = ImpairmentProfile::new()
+        .delay(Duration::from_millis(10))
+        .jitter(Duration::from_millis(5))
+        .loss(LossModel::GilbertElliott {
+            good_to_bad: 0.1,
+            bad_to_good: 0.5,
+            good_loss: 0.0,
+            bad_loss: 0.8,
+        })
+        .duplication(0.1)
+        .seed(42);
+
+    let now = Instant::now();
+    let schedule = |profile: ImpairmentProfile| {
+        let mut emulator = NetworkEmulator::new(profile);
+        (0..1000)
+            .map(|_| emulator.schedule(100, now))
+            .collect::<Vec<_>>()
+    };
+
+    // Seeded emulations are reproducible
+    let deliveries = schedule(profile.clone());
+    assert_eq!(deliveries, schedule(profile));
+
+    let lost = deliveries.iter().filter(|times| times.is_empty()).count();
+    assert!(lost > 0 && lost < 500);
+    assert!(deliveries.iter().any(|times| times.len() == 2));
+    assert!(deliveries.iter().flatten().all(|time| {
+        let delay = time.duration_since(now);
+        delay >= Duration::from_millis(5) && delay <= Duration::from_millis(15)
+    }));
+
+    // Packets exceeding the bandwidth are queued
+    let mut emulator = NetworkEmulator::new(ImpairmentProfile::new().bandwidth(8000));
+    assert_eq!(
+        emulator.schedule(500, now),
+        vec![now + Duration::from_millis(500)]
+    );
+    assert_eq!(
+        emulator.schedule(500, now),
+        vec![now + Duration::from_secs(1)]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Bad state loss probability must be in [0, 1]")]
+fn test_impairment_invalid_loss() {
+    ImpairmentProfile::new().loss(LossModel::GilbertElliott {
+        good_to_bad: 0.1,
+        bad_to_good: 0.5,
+        good_loss: 0.0,
+        bad_loss: 1.5,
+    });
+}
+
+#[test]
+#[should_panic(expected = "Duplication probability must be in [0, 1]")]
+fn test_impairment_invalid_duplication() {
+    ImpairmentProfile::new().duplication(f64::NAN);
+}
+
+#[test]
+fn test_impairment_trace() {
+    let trace = ImpairmentTrace::parse(
+        "# time_ms delay_ms jitter_ms loss bandwidth_kbps\n\
+         0 20 0 0.0 0\n\
+         \n\
+         1000 80 10 0.05 2000\n",
+    )
+    .unwrap();
+
+    assert_eq!(trace.steps().len(), 2);
+    assert_eq!(
+        trace.step_at(Duration::from_millis(500)).delay,
+        Duration::from_millis(20)
+    );
+
+    let step = trace.step_at(Duration::from_secs(5));
+    assert_eq!(step.delay, Duration::from_millis(80));
+    assert_eq!(step.loss, 0.05);
+    assert_eq!(step.bandwidth, Some(2_000_000));
+
+    assert!(ImpairmentTrace::parse("0 20 0 1.5 0").is_err());
+    assert!(ImpairmentTrace::parse("1000 20 0 0 0\n0 20 0 0 0").is_err());
+    assert!(ImpairmentTrace::parse("# empty").is_err());
+}
+
+#[tokio::test]
+async fn test_impairment_emulator_processor() {
+    let (mut input, mut output) = ImpairmentEmulator::new(
+        FrameBuffer,
+        ImpairmentProfile::new()
+            .delay(Duration::from_millis(30))
+            .jitter(Duration::from_millis(20))
+            .duplication(0.5)
+            .seed(7),
+    )
+    .split();
+
+    // Frames are in flight at the same time, rather than delayed one after the other
+    let start = Instant::now();
+    for frame_id in 0..20u8 {
+        assert!(input
+            .process(TestFrameData::with_buffer(&[frame_id]))
+            .await
+            .is_none());
+    }
+    assert!(output.in_flight_frames() > 20);
+
+    let mut received = Vec::new();
+    while output.in_flight_frames() > 0 {
+        let frame_data = output.process(TestFrameData::default()).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        received.push(frame_data.buffer[0]);
+    }
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    let mut sorted = received.clone();
+    sorted.sort_unstable();
+    assert_ne!(received, sorted);
+    sorted.dedup();
+    assert_eq!(sorted, (0..20).collect::<Vec<u8>>());
+
+    let (mut lossy, _) = ImpairmentEmulator::new(
+        FrameBuffer,
+        ImpairmentProfile::new().loss(LossModel::Random(1.0)),
+    )
+    .split();
+    let frame_data = lossy
+        .process(TestFrameData::with_buffer(b"frame"))
+        .await
+        .unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::EmulatedLoss));
+}
+
+#[tokio::test]
+async fn test_udp_impairment_proxy() {
+    let server_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let proxy = UdpImpairmentProxy::bind(
+        "127.0.0.1:0",
+        server_socket.local_addr().unwrap(),
+        ImpairmentProfile::new()
+            .delay(Duration::from_millis(20))
+            .duplication(1.0),
+    )
+    .await
+    .unwrap();
+
+    let client_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client_socket
+        .connect(proxy.local_addr().unwrap())
+        .await
+        .unwrap();
+    let proxy = proxy.spawn();
+
+    let start = Instant::now();
+    client_socket.send(b"ping").await.unwrap();
+
+    let mut packet_buffer = [0; 16];
+    for _ in 0..2 {
+        let (packet_size, proxy_address) =
+            server_socket.recv_from(&mut packet_buffer).await.unwrap();
+        assert_eq!(&packet_buffer[..packet_size], b"ping");
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        server_socket.send_to(b"pong", proxy_address).await.unwrap();
+    }
+
+    for _ in 0..2 {
+        let packet_size = client_socket.recv(&mut packet_buffer).await.unwrap();
+        assert_eq!(&packet_buffer[..packet_size], b"pong");
+    }
+
+    proxy.abort();
+}
+
+fn fast_backoff() -> Backoff {
+    Backoff::new(Duration::from_millis(10), Duration::from_millis(50))
+}
+
+#[tokio::test]
+async fn test_tcp_client_reconnection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let connection = ConnectionManager::new(TcpClient::new(listener.local_addr().unwrap()))
+        .backoff(fast_backoff());
+    let mut sender = TcpFrameSender::with_connection(FrameBuffer, connection);
+    let mut state = sender.connection_state();
+
+    let frame_data = sender
+        .process(TestFrameData::with_buffer(b"first"))
+        .await
+        .unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(*state.borrow(), ConnectionState::Connected);
+
+    let (server, _) = listener.accept().await.unwrap();
+    let mut receiver = TcpFrameReceiver::new(FrameBuffer, server);
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(&frame_data.buffer[..], b"first");
+    drop(receiver);
+
+    // Frames sent while the connection is down are reported as lost, without waiting for it
+    let mut lost = false;
+    for _ in 0..100 {
+        let start = Instant::now();
+        let frame_data = sender
+            .process(TestFrameData::with_buffer(b"lost"))
+            .await
+            .unwrap();
+        if frame_data.get_error() == Some(DropReason::ConnectionError) {
+            assert!(start.elapsed() < Duration::from_millis(500));
+            lost = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(lost);
+    assert_ne!(*state.borrow_and_update(), ConnectionState::Connected);
+
+    let (server, _) = listener.accept().await.unwrap();
+    let mut receiver = TcpFrameReceiver::new(FrameBuffer, server);
+
+    // Once lost, frames are not held back waiting for the connection
+    state
+        .wait_for(|state| *state == ConnectionState::Connected)
+        .await
+        .unwrap();
+    let frame_data = sender
+        .process(TestFrameData::with_buffer(b"second"))
+        .await
+        .unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(*state.borrow(), ConnectionState::Connected);
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(&frame_data.buffer[..], b"second");
+}
+
+#[tokio::test]
+async fn test_tcp_server_reconnection() {
+    let server = TcpServer::bind("127.0.0.1:0").await.unwrap();
+    let address = server.local_addr().unwrap();
+    let mut receiver =
+        TcpFrameReceiver::with_connection(FrameBuffer, ConnectionManager::new(server));
+
+    for payload in [&b"first"[..], &b"second"[..]] {
+        let mut sender =
+            TcpFrameSender::new(FrameBuffer, TcpStream::connect(address).await.unwrap());
+        sender
+            .process(TestFrameData::with_buffer(payload))
+            .await
+            .unwrap();
+
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], payload);
+
+        // The client going away is reported, then the next one is accepted
+        drop(sender);
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), Some(DropReason::ConnectionError));
+    }
+}
+
+#[tokio::test]
+async fn test_tcp_broadcast() {
+    let mut sender: TcpBroadcastSender<FrameBuffer, Stat> =
+        TcpBroadcastSender::bind(FrameBuffer, "127.0.0.1:0")
+            .await
+            .unwrap()
+            .frame_id_key(Stat::FrameId);
+    let mut events = sender.client_events();
+
+    // Clients are accepted once the first frame is processed
+    sender
+        .process(TestFrameData::with_buffer(b"before"))
+        .await
+        .unwrap();
+
+    let mut receivers = Vec::new();
+    for _ in 0..2 {
+        let stream = TcpStream::connect(sender.local_addr()).await.unwrap();
+        receivers.push(TcpFrameReceiver::new(FrameBuffer, stream).frame_id_key(Stat::FrameId));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ClientEvent::Joined(..)
+        ));
+    }
+    assert_eq!(sender.clients_count(), 2);
+
+    let mut frame_data = TestFrameData::with_buffer(b"broadcast");
+    frame_data.set(Stat::FrameId, 7);
+    sender.process(frame_data).await.unwrap();
+
+    for receiver in &mut receivers {
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(&frame_data.buffer[..], b"broadcast");
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(7));
+    }
+
+    // A leaving client is noticed on the next frame sent to it
+    drop(receivers.pop());
+    for _ in 0..100 {
+        sender
+            .process(TestFrameData::with_buffer(b"broadcast"))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        if sender.clients_count() == 1 {
+            break;
+        }
+    }
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClientEvent::Left(1, LeaveReason::Disconnected)
+    );
+}
+
+#[tokio::test]
+async fn test_tcp_broadcast_slow_client() {
+    let mut sender: TcpBroadcastSender<FrameBuffer, Stat> =
+        TcpBroadcastSender::bind(FrameBuffer, "127.0.0.1:0")
+            .await
+            .unwrap()
+            .queue_size(1)
+            .slow_client_policy(SlowClientPolicy::Disconnect);
+    let mut events = sender.client_events();
+
+    let client = TcpStream::connect(sender.local_addr()).await.unwrap();
+    sender
+        .process(TestFrameData::with_buffer(b"frame"))
+        .await
+        .unwrap();
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClientEvent::Joined(0, client.local_addr().unwrap())
+    );
+
+    // Frames are queued faster than the client task can send them
+    for _ in 0..2 {
+        sender
+            .process(TestFrameData::with_buffer(b"frame"))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClientEvent::Left(0, LeaveReason::TooSlow)
+    );
+    assert_eq!(sender.clients_count(), 0);
+}
+
+#[cfg(feature = "quic")]
+#[tokio::test]
+async fn test_quic_transmission() {
+    use crate::certificates::self_signed_certificate;
+    use crate::quic::{
+        endpoint::{client_endpoint, server_endpoint},
+        receiver::QuicFrameReceiver,
+        sender::QuicFrameSender,
+        QuicDelivery,
+    };
+
+    let (certificate, key) = self_signed_certificate(&["localhost"]).unwrap();
+    let server = server_endpoint("127.0.0.1:0".parse().unwrap(), certificate.clone(), key).unwrap();
+    let client = client_endpoint("127.0.0.1:0".parse().unwrap(), &[certificate]).unwrap();
+
+    let connecting = client
+        .connect(server.local_addr().unwrap(), "localhost")
+        .unwrap();
+    let (client_connection, server_connection) =
+        tokio::join!(connecting, async { server.accept().await.unwrap().await });
+
+    let mut sender = QuicFrameSender::new(
+        Buffer::Primary,
+        QuicDelivery::Stream,
+        server_connection.unwrap(),
+    )
+    .buffer(Buffer::Secondary, QuicDelivery::Datagram)
+    .timestamp_key(Stat::Timestamp);
+    let mut receiver = QuicFrameReceiver::new(Buffer::Primary, client_connection.unwrap())
+        .buffer(Buffer::Secondary)
+        .timeout(Duration::from_secs(1))
+        .timestamp_key(Stat::Timestamp)
+        .frame_id_key(Stat::FrameId);
+
+    for frame_id in 0..3u8 {
+        let mut frame_data = TestFrameData {
+            buffer: BytesMut::from(&vec![frame_id; 5000][..]),
+            secondary_buffer: BytesMut::from(&vec![frame_id + 100; 3000][..]),
+            ..Default::default()
+        };
+        frame_data.set(Stat::Timestamp, 1000 + frame_id as u128);
+        let frame_data = sender.process(frame_data).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], &vec![frame_id; 5000][..]);
+        assert_eq!(
+            &frame_data.secondary_buffer[..],
+            &vec![frame_id + 100; 3000][..]
+        );
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(frame_id as u128));
+        assert_eq!(
+            frame_data.get(&Stat::Timestamp),
+            Some(1000 + frame_id as u128)
+        );
+    }
+
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::NoCompleteFrames));
+}
+
+#[cfg(feature = "tls")]
+#[tokio::test]
+async fn test_tls_transmission() {
+    use crate::certificates::self_signed_certificate;
+    use crate::tls::{
+        client_config, client_config_with_auth, server_config_with_client_auth, TlsClient,
+        TlsServer,
+    };
+
+    let (server_certificate, server_key) = self_signed_certificate(&["localhost"]).unwrap();
+    let (client_certificate, client_key) = self_signed_certificate(&["client"]).unwrap();
+
+    let server_config = server_config_with_client_auth(
+        vec![server_certificate.clone()],
+        server_key,
+        std::slice::from_ref(&client_certificate),
+    )
+    .unwrap();
+
+    // Authenticated client
+    let server = TlsServer::bind("127.0.0.1:0", server_config.clone())
+        .await
+        .unwrap();
+    let client = TlsClient::new(
+        server.local_addr().unwrap(),
+        "localhost",
+        client_config_with_auth(
+            std::slice::from_ref(&server_certificate),
+            vec![client_certificate],
+            client_key,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let mut receiver =
+        TcpFrameReceiver::with_connection(FrameBuffer, ConnectionManager::new(server));
+    let mut sender = TcpFrameSender::with_connection(FrameBuffer, ConnectionManager::new(client));
+
+    let (sent, received) = tokio::join!(
+        sender.process(TestFrameData::with_buffer(b"encrypted")),
+        receiver.process(TestFrameData::default())
+    );
+    assert_eq!(sent.unwrap().get_error(), None);
+    let frame_data = received.unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(&frame_data.buffer[..], b"encrypted");
+
+    // Clients without a trusted certificate are refused
+    let server = TlsServer::bind("127.0.0.1:0", server_config).await.unwrap();
+    let client = TlsClient::new(
+        server.local_addr().unwrap(),
+        "localhost",
+        client_config(&[server_certificate]).unwrap(),
+    )
+    .unwrap();
+
+    let mut receiver = TcpFrameReceiver::with_connection(
+        FrameBuffer,
+        ConnectionManager::new(server).connection_timeout(Duration::from_millis(200)),
+    );
+    let mut sender = TcpFrameSender::with_connection(
+        FrameBuffer,
+        ConnectionManager::new(client)
+            .backoff(fast_backoff())
+            .connection_timeout(Duration::from_millis(200)),
+    );
+
+    let (_, received) = tokio::join!(
+        sender.process(TestFrameData::with_buffer(b"refused")),
+        receiver.process(TestFrameData::default())
+    );
+    assert_eq!(
+        received.unwrap().get_error(),
+        Some(DropReason::ConnectionError)
+    );
+}
+
+#[cfg(feature = "tls")]
+#[tokio::test]
+async fn test_tls_stalled_handshake() {
+    use crate::certificates::self_signed_certificate;
+    use crate::tls::{client_config, server_config, TlsClient, TlsServer};
+
+    let (server_certificate, server_key) = self_signed_certificate(&["localhost"]).unwrap();
+    let server = TlsServer::bind(
+        "127.0.0.1:0",
+        server_config(vec![server_certificate.clone()], server_key).unwrap(),
+    )
+    .await
+    .unwrap()
+    .handshake_timeout(Duration::from_millis(100));
+    let server_address = server.local_addr().unwrap();
+
+    // A client never starting the handshake does not prevent the next one from connecting
+    let _stalled_client = TcpStream::connect(server_address).await.unwrap();
+
+    let client = TlsClient::new(
+        server_address,
+        "localhost",
+        client_config(&[server_certificate]).unwrap(),
+    )
+    .unwrap();
+
+    let mut receiver =
+        TcpFrameReceiver::with_connection(FrameBuffer, ConnectionManager::new(server));
+    let mut sender = TcpFrameSender::with_connection(FrameBuffer, ConnectionManager::new(client));
+
+    let (sent, received) = tokio::join!(
+        sender.process(TestFrameData::with_buffer(b"encrypted")),
+        receiver.process(TestFrameData::default())
+    );
+    assert_eq!(sent.unwrap().get_error(), None);
+    let frame_data = received.unwrap();
+    assert_eq!(frame_data.get_error(), None);
+    assert_eq!(&frame_data.buffer[..], b"encrypted");
+}
+
+#[tokio::test]
+async fn test_loopback_transmission() {
+    let (link_sender, link_receiver) = link(4);
+    let mut sender = LoopbackFrameSender::new(FrameBuffer, link_sender)
+        .frame_id_key(Stat::FrameId)
+        .timestamp_key(Stat::Timestamp);
+    let mut receiver = LoopbackFrameReceiver::new(FrameBuffer, link_receiver)
+        .frame_id_key(Stat::FrameId)
+        .timestamp_key(Stat::Timestamp);
+
+    for frame_id in 0..3 {
+        let mut frame_data = TestFrameData::with_buffer(&[frame_id as u8; 16]);
+        frame_data.set(Stat::FrameId, frame_id);
+        frame_data.set(Stat::Timestamp, 1000 + frame_id);
+        let frame_data = sender.process(frame_data).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+    }
+
+    for frame_id in 0..3 {
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], &[frame_id as u8; 16]);
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(frame_id));
+        assert_eq!(frame_data.get(&Stat::Timestamp), Some(1000 + frame_id));
+    }
+
+    drop(sender);
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::ConnectionError));
+}
+
+#[tokio::test]
+async fn test_impaired_loopback() {
+    let profile = ImpairmentProfile::new()
+        .delay(Duration::from_millis(20))
+        .loss(LossModel::Random(0.5))
+        .seed(7);
+    let expected: Vec<u128> = {
+        let mut emulator = NetworkEmulator::new(profile.clone());
+        let now = Instant::now();
+        (0..20)
+            .filter(|_| !emulator.schedule(1, now).is_empty())
+            .collect()
+    };
+    assert!(!expected.is_empty() && expected.len() < 20);
+
+    let (link_sender, link_receiver) = link(32);
+    let mut sender = LoopbackFrameSender::new(FrameBuffer, link_sender).impairment(profile);
+    let mut receiver = LoopbackFrameReceiver::new(FrameBuffer, link_receiver);
+
+    let start = Instant::now();
+    for frame_id in 0..20u8 {
+        sender
+            .process(TestFrameData::with_buffer(&[frame_id]))
+            .await
+            .unwrap();
+    }
+    drop(sender);
+
+    let mut received = Vec::new();
+    loop {
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        if frame_data.get_error() == Some(DropReason::ConnectionError) {
+            break;
+        }
+        received.push(frame_data.buffer[0] as u128);
+    }
+
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(received, expected);
+}
+
+/// Feeds the estimator with frames of `frame_size` bytes sent every 10ms for 5 seconds through a
+/// link of the given capacity, reporting their arrivals every 50ms
+fn estimate_bandwidth(
+    estimator: &mut BandwidthEstimator,
+    frame_size: usize,
+    capacity: u64,
+) -> Vec<BandwidthUsage> {
+    let start = Instant::now();
+    let propagation = Duration::from_millis(20);
+    let transmission = Duration::from_secs_f64(frame_size as f64 * 8.0 / capacity as f64);
+
+    let mut link_free_at = Duration::ZERO;
+    let mut results = Vec::new();
+    let mut usages = Vec::new();
+
+    for frame in 1..=500 {
+        let sent_at = Duration::from_millis(10 * frame);
+        link_free_at = link_free_at.max(sent_at) + transmission;
+        results.push(FrameResult {
+            sent_at: start + sent_at,
+            size: frame_size,
+            arrival_time: Some(link_free_at + propagation),
+        });
+
+        if frame % 5 == 0 {
+            estimator.on_feedback(&results, start + link_free_at + propagation * 2);
+            usages.push(estimator.usage());
+            results.clear();
+        }
+    }
+
+    usages
+}
+
+#[test]
+fn test_bandwidth_estimation_overuse() {
+    // 4 Mbps sent through a 2 Mbps link
+    let mut estimator = BandwidthEstimator::new(4_000_000);
+    let usages = estimate_bandwidth(&mut estimator, 5000, 2_000_000);
+
+    assert!(usages.contains(&BandwidthUsage::Overusing));
+    assert!(estimator.target_bitrate() < 2_000_000);
+    assert!(estimator.target_bitrate() > 1_000_000);
+}
+
+#[test]
+fn test_bandwidth_estimation_increase() {
+    // 1 Mbps sent through a 10 Mbps link
+    let mut estimator = BandwidthEstimator::new(1_000_000).bitrate_range(100_000, 1_200_000);
+    let usages = estimate_bandwidth(&mut estimator, 1250, 10_000_000);
+
+    assert!(!usages.contains(&BandwidthUsage::Overusing));
+    assert_eq!(estimator.target_bitrate(), 1_200_000);
+}
+
+#[test]
+fn test_bandwidth_estimation_loss() {
+    let mut estimator = BandwidthEstimator::new(1_000_000);
+    let start = Instant::now();
+
+    let results: Vec<FrameResult> = (0..10)
+        .map(|frame| FrameResult {
+            sent_at: start + Duration::from_millis(10 * frame),
+            size: 1000,
+            arrival_time: (frame % 2 == 0).then(|| Duration::from_millis(10 * frame)),
+        })
+        .collect();
+
+    assert!(estimator.on_feedback(&results, start + Duration::from_millis(100)) < 1_000_000);
+}
+
+#[tokio::test]
+async fn test_bitrate_controller_feedback() {
+    let mut controller: BitrateController<FrameBuffer, Stat> =
+        BitrateController::bind(FrameBuffer, Stat::FrameId, "127.0.0.1:0")
+            .await
+            .unwrap()
+            .bitrate_key(Stat::Flags);
+    let mut target_bitrate = controller.target_bitrate();
+    assert_eq!(*target_bitrate.borrow_and_update(), 1_000_000);
+
+    let mut reporter = FeedbackReporter::connect(Stat::FrameId, controller.local_addr())
+        .await
+        .unwrap()
+        .interval(Duration::from_millis(20));
+
+    // Half of the frames are lost
+    for frame_id in 0..20 {
+        let mut frame_data = TestFrameData::with_buffer(&[0; 1000]);
+        frame_data.set(Stat::FrameId, frame_id);
+        let frame_data = controller.process(frame_data).await.unwrap();
+        assert!(frame_data.get(&Stat::Flags).is_some());
+
+        if frame_id % 2 == 0 {
+            reporter.process(frame_data).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    tokio::time::timeout(Duration::from_secs(1), target_bitrate.changed())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(*target_bitrate.borrow() < 1_000_000);
+}
+
+#[tokio::test]
+async fn test_bitrate_controller_lost_feedback() {
+    let mut controller: BitrateController<FrameBuffer, Stat> =
+        BitrateController::bind(FrameBuffer, Stat::FrameId, "127.0.0.1:0")
+            .await
+            .unwrap();
+    let mut target_bitrate = controller.target_bitrate();
+    target_bitrate.mark_unchanged();
+
+    let relay = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let mut reporter = FeedbackReporter::connect(Stat::FrameId, relay.local_addr().unwrap())
+        .await
+        .unwrap()
+        .interval(Duration::ZERO)
+        .repeated_arrivals(4);
+
+    let mut packet_buffer = vec![0; 2048];
+    for frame_id in 0..10u64 {
+        let mut frame_data = TestFrameData::with_buffer(&[0; 1000]);
+        frame_data.set(Stat::FrameId, frame_id as u128);
+        let frame_data = controller.process(frame_data).await.unwrap();
+        reporter.process(frame_data).await.unwrap();
+
+        // Each report repeats the latest arrivals already reported
+        let packet_size = relay.recv(&mut packet_buffer).await.unwrap();
+        let feedback = decode_feedback(&packet_buffer[..packet_size]).unwrap();
+        let reported_frames: Vec<u64> = feedback
+            .arrivals
+            .iter()
+            .map(|arrival| arrival.frame_id)
+            .collect();
+        assert_eq!(
+            reported_frames,
+            (frame_id.saturating_sub(4)..=frame_id).collect::<Vec<_>>()
+        );
+
+        // The reports of frames 3 and 4 are lost
+        if frame_id != 3 && frame_id != 4 {
+            relay
+                .send_to(&packet_buffer[..packet_size], controller.local_addr())
+                .await
+                .unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(target_bitrate.has_changed().unwrap());
+    assert!(*target_bitrate.borrow() >= 1_000_000);
+}
+
+#[tokio::test]
+async fn test_bitrate_controller_feedback_timeout() {
+    let mut controller: BitrateController<FrameBuffer, Stat> =
+        BitrateController::bind(FrameBuffer, Stat::FrameId, "127.0.0.1:0")
+            .await
+            .unwrap()
+            .feedback_timeout(Duration::from_millis(100));
+    let mut target_bitrate = controller.target_bitrate();
+    target_bitrate.mark_unchanged();
+
+    // No feedback is received at all
+    for frame_id in 0..5 {
+        let mut frame_data = TestFrameData::with_buffer(&[0; 1000]);
+        frame_data.set(Stat::FrameId, frame_id);
+        controller.process(frame_data).await.unwrap();
+    }
+
+    tokio::time::timeout(Duration::from_secs(1), target_bitrate.changed())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(*target_bitrate.borrow() < 1_000_000);
+}
+
+#[test]
+fn test_clock_offset_estimation() {
+    use remotia_core::common::network::clock::ClockSyncPacket;
+
+    // The remote clock is 5ms ahead and drifts by 100 ppm
+    let remote_time = |local_time: u64| local_time + 5000 + local_time / 10_000;
+
+    let mut estimator = OffsetEstimator::new(4);
+    for exchange in 0..40u64 {
+        let origin = exchange * 100_000;
+        let (outbound, inbound) = match exchange % 4 {
+            0 => (200, 200),
+            1 => (3000, 500),
+            2 => (400, 9000),
+            _ => (1500, 1500),
+        };
+
+        let receive_timestamp = remote_time(origin + outbound);
+        let response = ClockSyncPacket {
+            origin_timestamp: origin,
+            receive_timestamp,
+            transmit_timestamp: receive_timestamp + 50,
+        };
+        let arrival = origin + outbound + 50 + inbound;
+        estimator.push(ClockSample::new(&response, arrival));
+    }
+
+    let estimate = estimator.estimate().unwrap();
+    assert!(estimate.round_trip <= 400.0);
+    assert!((estimate.skew - 0.0001).abs() < 0.00002);
+
+    let local_time = 4_500_000;
+    let error = estimate.remote_time(local_time) as i128 - remote_time(local_time as u64) as i128;
+    assert!(error.abs() < 100, "Estimation error: {}us", error);
+}
+
+#[tokio::test]
+async fn test_clock_synchronization() {
+    use remotia_core::common::helpers::time::{now_timestamp, Clock};
+
+    let server = ClockSyncServer::bind("127.0.0.1:0").await.unwrap();
+    let server_address = server.local_addr().unwrap();
+    let server = server.spawn();
+
+    let synchronizer = ClockSynchronizer::connect(server_address)
+        .await
+        .unwrap()
+        .interval(Duration::from_millis(10));
+    let clock = synchronizer.clock();
+    assert!(!clock.is_synchronized());
+    let synchronizer = synchronizer.spawn();
+
+    for _ in 0..100 {
+        if clock.is_synchronized() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Both ends share the same clock
+    let estimate = clock.estimate().unwrap();
+    assert!(estimate.offset.abs() < 1000.0);
+    assert!(clock.now().abs_diff(now_timestamp()) <= 1);
+
+    synchronizer.abort();
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_tcp_pacing() {
+    let (client, server) = tcp_pair().await;
+
+    // 100 KB at 1 MB/s
+    let mut sender = TcpFrameSender::new(FrameBuffer, client)
+        .pacing(Pacing::Rate(8_000_000))
+        .pacing_delay_key(Stat::Timestamp);
+    let mut receiver = TcpFrameReceiver::new(FrameBuffer, server);
+
+    let payload: Vec<u8> = (0..100_000).map(|value| (value % 251) as u8).collect();
+    let start = Instant::now();
+    let (sent, received) = tokio::join!(
+        sender.process(TestFrameData::with_buffer(&payload)),
+        receiver.process(TestFrameData::default())
+    );
+
+    let pacing_delay = sent.unwrap().get(&Stat::Timestamp).unwrap();
+    assert!((80_000..200_000).contains(&pacing_delay));
+    assert!(start.elapsed() >= Duration::from_millis(80));
+    assert_eq!(&received.unwrap().buffer[..], &payload[..]);
+}
+
+#[tokio::test]
+async fn test_remvsp_pacing() {
+    let receiver_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let sender_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    sender_socket
+        .connect(receiver_socket.local_addr().unwrap())
+        .await
+        .unwrap();
+
+    let mut sender = RemVSPFrameSender::new(FrameBuffer, sender_socket)
+        .mtu(1000)
+        .pacing(Pacing::Interval(Duration::from_millis(50)))
+        .pacing_delay_key(Stat::Timestamp);
+    let mut receiver =
+        RemVSPFrameReceiver::new(FrameBuffer, receiver_socket).timeout(Duration::from_millis(500));
+
+    for _ in 0..2 {
+        let payload: Vec<u8> = (0..20_000).map(|value| (value % 251) as u8).collect();
+        let frame_data = sender
+            .process(TestFrameData::with_buffer(&payload))
+            .await
+            .unwrap();
+
+        // The last fragment leaves right before the end of the interval
+        let pacing_delay = frame_data.get(&Stat::Timestamp).unwrap();
+        assert!(
+            (40_000..150_000).contains(&pacing_delay),
+            "{}",
+            pacing_delay
+        );
+
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], &payload[..]);
+    }
+}
+
+#[tokio::test]
+async fn test_jitter_buffer_playout() {
+    let (mut input, mut output) = JitterBuffer::timestamp(Stat::Timestamp)
+        .delay_range(Duration::from_millis(40), Duration::from_millis(100))
+        .delay_key(Stat::Flags)
+        .split::<TestFrameData>();
+
+    // Frames produced every 20ms, the fourth one overtaken by the fifth
+    let jitters = [0, 15, 5, 30, 0, 10, 25, 5, 0, 20];
+    let start = Instant::now();
+    let network = tokio::spawn(async move {
+        let mut arrivals: Vec<(u64, u128)> = jitters
+            .iter()
+            .enumerate()
+            .map(|(frame, jitter)| (frame as u64 * 20 + jitter, 1000 + frame as u128 * 20))
+            .collect();
+        arrivals.sort();
+
+        for (arrival, timestamp) in arrivals {
+            tokio::time::sleep_until((start + Duration::from_millis(arrival)).into()).await;
+
+            let mut frame_data = TestFrameData::default();
+            frame_data.set(Stat::Timestamp, timestamp);
+            assert!(input.process(frame_data).await.is_none());
+        }
+    });
+
+    let mut releases = Vec::new();
+    for _ in 0..jitters.len() {
+        let frame_data = output.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert!(frame_data.get(&Stat::Flags).is_some());
+        releases.push((frame_data.get(&Stat::Timestamp).unwrap(), Instant::now()));
+    }
+    network.await.unwrap();
+
+    // Released in order, at the pace they have been produced
+    let (first_timestamp, first_release) = releases[0];
+    for (timestamp, release) in releases.iter().skip(1) {
+        let expected = Duration::from_millis((timestamp - first_timestamp) as u64);
+        let actual = release.duration_since(first_release);
+        assert!(
+            actual.abs_diff(expected) < Duration::from_millis(10),
+            "Frame {} released after {:?}",
+            timestamp,
+            actual
+        );
+    }
+    let timestamps: Vec<u128> = releases.iter().map(|(timestamp, _)| *timestamp).collect();
+    assert_eq!(
+        timestamps,
+        (0..10).map(|frame| 1000 + frame * 20).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_jitter_buffer_stale_frames() {
+    let (mut input, mut output) = JitterBuffer::sequence(Stat::FrameId, Duration::from_millis(10))
+        .delay_range(Duration::ZERO, Duration::ZERO)
+        .split::<TestFrameData>();
+
+    let frame = |frame_id| {
+        let mut frame_data = TestFrameData::default();
+        frame_data.set(Stat::FrameId, frame_id);
+        frame_data
+    };
+
+    assert!(input.process(frame(10)).await.is_none());
+    let frame_data = output.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get(&Stat::FrameId), Some(10));
+
+    // Older than the released frame
+    let frame_data = input.process(frame(9)).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::StaleFrame));
+
+    // Due in 20ms, then past its playout time
+    assert!(input.process(frame(12)).await.is_none());
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let frame_data = input.process(frame(11)).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::StaleFrame));
+
+    let frame_data = output.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get(&Stat::FrameId), Some(12));
+    assert_eq!(output.held_frames(), 0);
+
+    // Without a media time, keeping the reason of frames already dropped
+    let frame_data = input.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(
+        frame_data.get_error(),
+        Some(DropReason::InvalidPacketHeader)
+    );
+
+    let mut frame_data = TestFrameData::default();
+    frame_data.report_error(DropReason::NoCompleteFrames);
+    let frame_data = input.process(frame_data).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::NoCompleteFrames));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unix_transmission() {
+    use crate::unix::{UnixClient, UnixFrameReceiver, UnixFrameSender, UnixServer};
+
+    let path = std::env::temp_dir().join(format!("remotia-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let server = UnixServer::bind(&path).unwrap();
+    let mut receiver: UnixFrameReceiver<FrameBuffer, Option<Stat>> =
+        TcpFrameReceiver::with_connection(FrameBuffer, ConnectionManager::new(server))
+            .frame_id_key(Stat::FrameId);
+    let mut sender: UnixFrameSender<FrameBuffer, Option<Stat>> = TcpFrameSender::with_connection(
+        FrameBuffer,
+        ConnectionManager::new(UnixClient::new(&path)).backoff(fast_backoff()),
+    )
+    .frame_id_key(Stat::FrameId);
+
+    for frame_id in 0..3 {
+        let mut frame_data = TestFrameData::with_buffer(b"frame");
+        frame_data.set(Stat::FrameId, frame_id);
+        let frame_data = sender.process(frame_data).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+
+        let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], b"frame");
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(frame_id));
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "websocket")]
+#[tokio::test]
+async fn test_websocket_transmission() {
+    use crate::websocket::{
+        accept, connect, receiver::WebSocketFrameReceiver, sender::WebSocketFrameSender,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    let (client, server) = tokio::join!(connect(&url), async {
+        accept(listener.accept().await.unwrap().0).await
+    });
+
+    let mut sender = WebSocketFrameSender::new(FrameBuffer, server.unwrap())
+        .frame_id_key(Stat::FrameId)
+        .flags_key(Stat::Flags);
+    let mut receiver = WebSocketFrameReceiver::new(FrameBuffer, client.unwrap())
+        .frame_id_key(Stat::FrameId)
+        .flags_key(Stat::Flags);
+
+    let payloads: [&[u8]; 3] = [b"first", b"", &[7; 100_000]];
+    for (frame_id, payload) in payloads.iter().enumerate() {
+        let mut frame_data = TestFrameData::with_buffer(payload);
+        frame_data.set(Stat::FrameId, frame_id as u128);
+        frame_data.set(Stat::Flags, 2);
+        sender.process(frame_data).await.unwrap();
+    }
+
+    for (frame_id, payload) in payloads.iter().enumerate() {
+        let frame_data = receiver
+            .process(TestFrameData::with_buffer(b"previous content"))
+            .await
+            .unwrap();
+        assert_eq!(frame_data.get_error(), None);
+        assert_eq!(&frame_data.buffer[..], *payload);
+        assert_eq!(frame_data.get(&Stat::FrameId), Some(frame_id as u128));
+        assert_eq!(frame_data.get(&Stat::Flags), Some(2));
+    }
+
+    drop(sender);
+    let frame_data = receiver.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::ConnectionError));
+}
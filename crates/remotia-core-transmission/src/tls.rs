@@ -0,0 +1,198 @@
+//! TLS encryption of the streams carrying frames between `TcpFrameSender` and
+//! `TcpFrameReceiver`, with optional authentication of the clients.
+
+use std::{error::Error, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use log::{info, warn};
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio_rustls::{
+    client,
+    rustls::{
+        crypto::{ring, CryptoProvider},
+        server::WebPkiClientVerifier,
+        ClientConfig, RootCertStore, ServerConfig,
+    },
+    server, TlsAcceptor, TlsConnector,
+};
+
+use crate::connection::Connector;
+
+pub use tokio_rustls::rustls;
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn invalid_input(error: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+fn root_store(trusted_certificates: &[CertificateDer<'static>]) -> io::Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for certificate in trusted_certificates {
+        roots.add(certificate.clone()).map_err(invalid_input)?;
+    }
+
+    Ok(roots)
+}
+
+/// Configuration of a server authenticated by the given certificate chain
+pub fn server_config(
+    certificate_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> io::Result<Arc<ServerConfig>> {
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(invalid_input)?
+        .with_no_client_auth()
+        .with_single_cert(certificate_chain, key)
+        .map_err(invalid_input)?;
+
+    Ok(Arc::new(config))
+}
+
+/// Configuration of a server only accepting the clients authenticated by one of the trusted
+/// certificates
+pub fn server_config_with_client_auth(
+    certificate_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    trusted_client_certificates: &[CertificateDer<'static>],
+) -> io::Result<Arc<ServerConfig>> {
+    let roots = root_store(trusted_client_certificates)?;
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider())
+        .build()
+        .map_err(invalid_input)?;
+
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(invalid_input)?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certificate_chain, key)
+        .map_err(invalid_input)?;
+
+    Ok(Arc::new(config))
+}
+
+/// Configuration of a client connecting to the servers authenticated by one of the trusted
+/// certificates
+pub fn client_config(
+    trusted_server_certificates: &[CertificateDer<'static>],
+) -> io::Result<Arc<ClientConfig>> {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(invalid_input)?
+        .with_root_certificates(root_store(trusted_server_certificates)?)
+        .with_no_client_auth();
+
+    Ok(Arc::new(config))
+}
+
+/// Configuration of a client authenticating itself with the given certificate chain
+pub fn client_config_with_auth(
+    trusted_server_certificates: &[CertificateDer<'static>],
+    certificate_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> io::Result<Arc<ClientConfig>> {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(invalid_input)?
+        .with_root_certificates(root_store(trusted_server_certificates)?)
+        .with_client_auth_cert(certificate_chain, key)
+        .map_err(invalid_input)?;
+
+    Ok(Arc::new(config))
+}
+
+/// Connects to a listening TLS server
+pub struct TlsClient<A> {
+    address: A,
+    server_name: ServerName<'static>,
+    connector: TlsConnector,
+}
+
+impl<A> TlsClient<A> {
+    /// Connects to `address`, expecting the server to be authenticated as `server_name`
+    pub fn new(address: A, server_name: &str, config: Arc<ClientConfig>) -> io::Result<Self> {
+        Ok(Self {
+            address,
+            server_name: ServerName::try_from(server_name.to_string()).map_err(invalid_input)?,
+            connector: TlsConnector::from(config),
+        })
+    }
+}
+
+#[async_trait]
+impl<A> Connector for TlsClient<A>
+where
+    A: ToSocketAddrs + Clone + Send + Sync + 'static,
+{
+    type Stream = client::TlsStream<TcpStream>;
+
+    async fn connect(&mut self) -> io::Result<Self::Stream> {
+        let stream = TcpStream::connect(self.address.clone()).await?;
+        let peer = stream.peer_addr()?;
+
+        let stream = self
+            .connector
+            .connect(self.server_name.clone(), stream)
+            .await?;
+        info!("Established TLS connection to {}", peer);
+
+        Ok(stream)
+    }
+}
+
+/// Accepts one TLS client at a time, waiting for the next one whenever the connection is lost.
+/// Clients failing the handshake, or not completing it within the handshake timeout, are
+/// skipped in favour of the next ones.
+pub struct TlsServer {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshake_timeout: Duration,
+}
+
+impl TlsServer {
+    pub async fn bind(address: impl ToSocketAddrs, config: Arc<ServerConfig>) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+            acceptor: TlsAcceptor::from(config),
+            handshake_timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Sets how long a client may take to complete the handshake (5 seconds by default)
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+#[async_trait]
+impl Connector for TlsServer {
+    type Stream = server::TlsStream<TcpStream>;
+
+    async fn connect(&mut self) -> io::Result<Self::Stream> {
+        loop {
+            let (stream, address) = self.listener.accept().await?;
+            let handshake = self.acceptor.accept(stream);
+
+            match tokio::time::timeout(self.handshake_timeout, handshake).await {
+                Ok(Ok(stream)) => {
+                    info!("Established TLS connection from {}", address);
+                    return Ok(stream);
+                }
+                Ok(Err(error)) => warn!("TLS handshake with {} failed: {}", address, error),
+                Err(_) => warn!(
+                    "TLS handshake with {} not completed within {:?}",
+                    address, self.handshake_timeout
+                ),
+            }
+        }
+    }
+}
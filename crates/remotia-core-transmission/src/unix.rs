@@ -0,0 +1,64 @@
+//! Transmission over Unix domain sockets, for pipelines split on the same host. Frames are sent
+//! and received by `TcpFrameSender` and `TcpFrameReceiver` over a `UnixStream`.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use log::info;
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::{connection::Connector, receiver::TcpFrameReceiver, sender::TcpFrameSender};
+
+pub type UnixFrameSender<K, P = ()> = TcpFrameSender<K, P, UnixStream>;
+pub type UnixFrameReceiver<K, P = ()> = TcpFrameReceiver<K, P, UnixStream>;
+
+/// Connects to a listening socket
+pub struct UnixClient {
+    path: PathBuf,
+}
+
+impl UnixClient {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl Connector for UnixClient {
+    type Stream = UnixStream;
+
+    async fn connect(&mut self) -> io::Result<UnixStream> {
+        let stream = UnixStream::connect(&self.path).await?;
+        info!("Connected to {}", self.path.display());
+        Ok(stream)
+    }
+}
+
+/// Accepts one client at a time, waiting for the next one whenever the connection is lost
+pub struct UnixServer {
+    listener: UnixListener,
+}
+
+impl UnixServer {
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            listener: UnixListener::bind(path)?,
+        })
+    }
+}
+
+#[async_trait]
+impl Connector for UnixServer {
+    type Stream = UnixStream;
+
+    async fn connect(&mut self) -> io::Result<UnixStream> {
+        let (stream, _) = self.listener.accept().await?;
+        info!("Accepted connection");
+        Ok(stream)
+    }
+}
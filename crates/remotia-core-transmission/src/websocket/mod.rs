@@ -0,0 +1,32 @@
+//! Transmission of frames over WebSocket, allowing browsers to receive them. Each frame is sent
+//! as a single binary message made of a `FrameHeader` followed by the buffer content.
+
+use std::io;
+
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
+
+pub mod receiver;
+pub mod sender;
+
+fn to_io_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+/// Completes the WebSocket handshake of a client which connected to a server
+pub async fn accept(stream: TcpStream) -> io::Result<WebSocketStream<TcpStream>> {
+    tokio_tungstenite::accept_async(stream)
+        .await
+        .map_err(to_io_error)
+}
+
+/// Connects to a WebSocket server, e.g. `ws://127.0.0.1:8080`
+pub async fn connect(url: &str) -> io::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    tokio_tungstenite::connect_async(url)
+        .await
+        .map(|(stream, _)| stream)
+        .map_err(to_io_error)
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use log::{debug, warn};
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowMutFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+use crate::framing::{FrameHeader, HeaderKeys};
+
+/// Receives frames sent by a `WebSocketFrameSender`, resizing the buffer to the message length.
+///
+/// Messages whose length does not match their header are reported as
+/// `DropReason::InvalidWholeFrameHeader`, while `DropReason::ConnectionError` is reported once
+/// the connection is closed.
+pub struct WebSocketFrameReceiver<K, P, S> {
+    buffer_key: K,
+    header_keys: HeaderKeys<Option<P>>,
+    socket: WebSocketStream<S>,
+}
+
+impl<K, P, S> WebSocketFrameReceiver<K, P, S> {
+    pub fn new(buffer_key: K, socket: WebSocketStream<S>) -> Self {
+        Self {
+            buffer_key,
+            header_keys: HeaderKeys::default(),
+            socket,
+        }
+    }
+
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
+}
+
+#[async_trait]
+impl<F, K, P, S> FrameProcessor<F> for WebSocketFrameReceiver<K, P, S>
+where
+    K: Send,
+    P: Copy + Send,
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+    F: BorrowMutFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let message = loop {
+            match self.socket.next().await {
+                Some(Ok(Message::Binary(message))) => break message,
+                Some(Ok(Message::Close(_))) | None => {
+                    debug!("Connection closed");
+                    frame_data.report_error(DropReason::ConnectionError);
+                    return Some(frame_data);
+                }
+                Some(Ok(message)) => debug!("Ignoring non-binary message: {:?}", message),
+                Some(Err(error)) => {
+                    warn!("Unable to receive frame: {}", error);
+                    frame_data.report_error(DropReason::ConnectionError);
+                    return Some(frame_data);
+                }
+            }
+        };
+
+        let header = match message.get(..FrameHeader::SIZE) {
+            Some(header) => FrameHeader::decode(header.try_into().unwrap()),
+            None => {
+                frame_data.report_error(DropReason::InvalidWholeFrameHeader);
+                return Some(frame_data);
+            }
+        };
+
+        let payload = &message[FrameHeader::SIZE..];
+        if payload.len() != header.length as usize {
+            warn!(
+                "Frame length mismatch: {} bytes declared, {} received",
+                header.length,
+                payload.len()
+            );
+            frame_data.report_error(DropReason::InvalidWholeFrameHeader);
+            return Some(frame_data);
+        }
+
+        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        buffer.clear();
+        buffer.extend_from_slice(payload);
+
+        self.header_keys.apply(&header, &mut frame_data);
+
+        Some(frame_data)
+    }
+}
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use futures_util::SinkExt;
+use log::warn;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowFrameProperties, FrameError, FrameProcessor, FrameProperties},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+use crate::framing::{FrameHeader, HeaderKeys};
+
+/// Sends the content of a buffer as a binary WebSocket message, preceded by a `FrameHeader`
+/// carrying its length and optionally the frame id, timestamp and flags read from the frame
+/// properties. Frames which cannot be sent are reported as `DropReason::ConnectionError`.
+pub struct WebSocketFrameSender<K, P, S> {
+    buffer_key: K,
+    header_keys: HeaderKeys<Option<P>>,
+    socket: WebSocketStream<S>,
+}
+
+impl<K, P, S> WebSocketFrameSender<K, P, S> {
+    pub fn new(buffer_key: K, socket: WebSocketStream<S>) -> Self {
+        Self {
+            buffer_key,
+            header_keys: HeaderKeys::default(),
+            socket,
+        }
+    }
+
+    pub fn frame_id_key(mut self, key: P) -> Self {
+        self.header_keys.frame_id = Some(key);
+        self
+    }
+
+    pub fn timestamp_key(mut self, key: P) -> Self {
+        self.header_keys.timestamp = Some(key);
+        self
+    }
+
+    pub fn flags_key(mut self, key: P) -> Self {
+        self.header_keys.flags = Some(key);
+        self
+    }
+}
+
+#[async_trait]
+impl<F, K, P, S> FrameProcessor<F> for WebSocketFrameSender<K, P, S>
+where
+    K: Send,
+    P: Copy + Send,
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+    F: BorrowFrameProperties<K, BytesMut>
+        + FrameProperties<P, u128>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        let mut header = match FrameHeader::new(buffer.len()) {
+            Ok(header) => header,
+            Err(_) => {
+                warn!("Frame of {} bytes is too large to be sent", buffer.len());
+                frame_data.report_error(DropReason::OversizedFrame);
+                return Some(frame_data);
+            }
+        };
+        self.header_keys.fill(&mut header, &frame_data);
+
+        let mut message = Vec::with_capacity(FrameHeader::SIZE + buffer.len());
+        message.extend_from_slice(&header.encode());
+        message.extend_from_slice(buffer);
+
+        if let Err(error) = self.socket.send(Message::binary(message)).await {
+            warn!("Unable to send frame: {}", error);
+            frame_data.report_error(DropReason::ConnectionError);
+        }
+
+        Some(frame_data)
+    }
+}
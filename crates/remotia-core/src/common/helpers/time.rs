@@ -6,3 +6,25 @@ pub fn now_timestamp() -> u128 {
         .unwrap()
         .as_millis()
 }
+
+pub fn now_timestamp_micros() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_micros()
+}
+
+/// Source of timestamps, in milliseconds since the Unix epoch
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u128;
+}
+
+/// Clock of the local host
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u128 {
+        now_timestamp()
+    }
+}
@@ -1,5 +1,5 @@
 // pub mod profiling;
 // pub mod command_line;
-// pub mod network;
+pub mod network;
 pub mod helpers;
 // pub mod feedback;
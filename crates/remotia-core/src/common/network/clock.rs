@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// Timestamps exchanged to estimate the offset between the clocks of two hosts, in microseconds
+/// since the Unix epoch. Requests only carry the origin timestamp.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ClockSyncPacket {
+    pub origin_timestamp: u64,
+    pub receive_timestamp: u64,
+    pub transmit_timestamp: u64,
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Arrival of a frame at the receiver, in microseconds since the receiver started reporting
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameArrival {
+    pub frame_id: u64,
+    pub arrival_time: u64,
+}
+
+/// Report periodically sent back by the receiver, listing the frames received since the
+/// previous one along with the latest ones already reported
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransportFeedback {
+    pub arrivals: Vec<FrameArrival>,
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+pub mod clock;
+pub mod congestion;
 pub mod remvsp;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -2,14 +2,28 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct RemVSPFrameHeader {
+    pub frame_id: u64,
     pub frame_fragments_count: u16,
     pub fragment_size: u16,
-    pub capture_timestamp: u128
+    pub frame_length: u32,
+
+    /// Data fragments protected by each parity fragment, 0 if FEC is disabled
+    pub fec_group_size: u16,
+    pub capture_timestamp: u128,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RemVSPFrameFragment {
     pub frame_header: RemVSPFrameHeader,
     pub fragment_id: u16,
-    pub data: Vec<u8>
-}
\ No newline at end of file
+    pub data: Vec<u8>,
+}
+
+/// Retransmission request sent by the receiver for fragments it has not received.
+/// No fragments are listed for a frame none of whose fragments has been received,
+/// requesting all of them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemVSPNack {
+    pub frame_id: u64,
+    pub fragment_ids: Vec<u16>,
+}
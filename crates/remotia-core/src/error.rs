@@ -1,4 +1,4 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Copy)]
@@ -41,4 +41,25 @@ pub enum DropReason {
 
     #[error("No available buffers")]
     NoAvailableBuffers,
+
+    #[error("No new captured frames")]
+    NoCapturedFrames,
+
+    #[error("Capture error")]
+    CaptureError,
+
+    #[error("Unchanged frame")]
+    UnchangedFrame,
+
+    #[error("Unrecoverable frame")]
+    UnrecoverableFrame,
+
+    #[error("Frame lost by the network emulator")]
+    EmulatedLoss,
+
+    #[error("Frame too large to be sent")]
+    OversizedFrame,
+
+    #[error("Frame buffer of unexpected size")]
+    InvalidFrameSize,
 }
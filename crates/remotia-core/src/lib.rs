@@ -10,6 +10,6 @@ pub mod pipeline;
 
 pub mod traits;
 // pub mod types;
-// pub mod error;
+pub mod error;
 
 pub mod processors;
@@ -33,4 +33,36 @@ pub trait BorrowMutFrameProperties<K, V> {
 pub trait FrameError<E> {
     fn report_error(&mut self, error: E);
     fn get_error(&self) -> Option<E>;
-}
\ No newline at end of file
+}
+
+/// Key of a property that a processor only reads or writes when configured to: `Option<K>` for
+/// a property of key `K`, or `()` when the processor has no such property, in which case the
+/// frames are not required to implement `FrameProperties` at all
+pub trait OptionalPropertyKey<F, V>: Copy + Send {
+    fn set_property(&self, frame_data: &mut F, value: V);
+    fn get_property(&self, frame_data: &F) -> Option<V>;
+}
+
+impl<F, V> OptionalPropertyKey<F, V> for () {
+    fn set_property(&self, _frame_data: &mut F, _value: V) {}
+
+    fn get_property(&self, _frame_data: &F) -> Option<V> {
+        None
+    }
+}
+
+impl<F, K, V> OptionalPropertyKey<F, V> for Option<K>
+where
+    K: Copy + Send,
+    F: FrameProperties<K, V>,
+{
+    fn set_property(&self, frame_data: &mut F, value: V) {
+        if let Some(key) = self {
+            frame_data.set(*key, value);
+        }
+    }
+
+    fn get_property(&self, frame_data: &F) -> Option<V> {
+        frame_data.get(self.as_ref()?)
+    }
+}
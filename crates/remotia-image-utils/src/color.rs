@@ -0,0 +1,42 @@
+use crate::format::PixelFormat;
+
+/// Converts an RGB triplet to limited range BT.601 YUV
+pub fn rgb_to_yuv(r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+
+    let y = ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16;
+    let u = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+    let v = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+
+    (clamp(y), clamp(u), clamp(v))
+}
+
+/// Converts a limited range BT.601 YUV triplet to RGB
+pub fn yuv_to_rgb(y: u8, u: u8, v: u8) -> (u8, u8, u8) {
+    let c = y as i32 - 16;
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+
+    let r = (298 * c + 409 * e + 128) >> 8;
+    let g = (298 * c - 100 * d - 208 * e + 128) >> 8;
+    let b = (298 * c + 516 * d + 128) >> 8;
+
+    (clamp(r), clamp(g), clamp(b))
+}
+
+/// Returns, for each plane of the format, the bytes of a single pixel of the given RGBA color
+pub fn plane_pixels(format: PixelFormat, rgba: [u8; 4]) -> Vec<Vec<u8>> {
+    let [r, g, b, a] = rgba;
+    match format {
+        PixelFormat::Rgba => vec![vec![r, g, b, a]],
+        PixelFormat::Bgra => vec![vec![b, g, r, a]],
+        PixelFormat::Yuv420p => {
+            let (y, u, v) = rgb_to_yuv(r, g, b);
+            vec![vec![y], vec![u], vec![v]]
+        }
+    }
+}
+
+pub(crate) fn clamp(value: i32) -> u8 {
+    value.clamp(0, 255) as u8
+}
@@ -0,0 +1,126 @@
+use crate::{
+    color::{rgb_to_yuv, yuv_to_rgb},
+    format::PixelFormat,
+};
+
+/// Converts a frame between pixel formats. `dst` must be exactly
+/// `dst_format.buffer_size(width, height)` bytes long.
+pub fn convert_frame(
+    src: &[u8],
+    src_format: PixelFormat,
+    dst: &mut [u8],
+    dst_format: PixelFormat,
+    resolution: (usize, usize),
+) {
+    let (width, height) = resolution;
+
+    assert!(src.len() >= src_format.buffer_size(width, height));
+    assert_eq!(dst.len(), dst_format.buffer_size(width, height));
+
+    match (src_format, dst_format) {
+        (src_format, dst_format) if src_format == dst_format => {
+            dst.copy_from_slice(&src[..dst.len()])
+        }
+        (PixelFormat::Yuv420p, packed_format) => {
+            yuv420p_to_packed(src, dst, packed_format, width, height)
+        }
+        (packed_format, PixelFormat::Yuv420p) => {
+            packed_to_yuv420p(src, packed_format, dst, width, height)
+        }
+        (_, _) => swap_red_blue(src, dst),
+    }
+}
+
+/// Byte offsets of the red, green and blue channels inside a packed pixel
+fn rgb_offsets(format: PixelFormat) -> (usize, usize, usize) {
+    match format {
+        PixelFormat::Rgba => (0, 1, 2),
+        PixelFormat::Bgra => (2, 1, 0),
+        PixelFormat::Yuv420p => panic!("{:?} is not a packed format", format),
+    }
+}
+
+fn swap_red_blue(src: &[u8], dst: &mut [u8]) {
+    for (src_pixel, dst_pixel) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+        dst_pixel.copy_from_slice(&[src_pixel[2], src_pixel[1], src_pixel[0], src_pixel[3]]);
+    }
+}
+
+fn packed_to_yuv420p(
+    src: &[u8],
+    src_format: PixelFormat,
+    dst: &mut [u8],
+    width: usize,
+    height: usize,
+) {
+    let (r_offset, g_offset, b_offset) = rgb_offsets(src_format);
+    let planes = PixelFormat::Yuv420p.planes(width, height);
+    let (luma, chroma) = (planes[0], planes[1]);
+
+    let pixel_at = |x: usize, y: usize| {
+        let offset = (y * width + x) * 4;
+        (
+            src[offset + r_offset] as u32,
+            src[offset + g_offset] as u32,
+            src[offset + b_offset] as u32,
+        )
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = pixel_at(x, y);
+            dst[y * width + x] = rgb_to_yuv(r as u8, g as u8, b as u8).0;
+        }
+    }
+
+    for chroma_y in 0..chroma.height {
+        for chroma_x in 0..chroma.width {
+            let mut sum = (0, 0, 0);
+            let mut count = 0;
+
+            for y in (chroma_y * 2)..(chroma_y * 2 + 2).min(height) {
+                for x in (chroma_x * 2)..(chroma_x * 2 + 2).min(width) {
+                    let (r, g, b) = pixel_at(x, y);
+                    sum = (sum.0 + r, sum.1 + g, sum.2 + b);
+                    count += 1;
+                }
+            }
+
+            let average = |value: u32| ((value + count / 2) / count) as u8;
+            let (_, u, v) = rgb_to_yuv(average(sum.0), average(sum.1), average(sum.2));
+
+            let index = chroma_y * chroma.width + chroma_x;
+            dst[luma.size() + index] = u;
+            dst[luma.size() + chroma.size() + index] = v;
+        }
+    }
+}
+
+fn yuv420p_to_packed(
+    src: &[u8],
+    dst: &mut [u8],
+    dst_format: PixelFormat,
+    width: usize,
+    height: usize,
+) {
+    let (r_offset, g_offset, b_offset) = rgb_offsets(dst_format);
+    let planes = PixelFormat::Yuv420p.planes(width, height);
+    let (luma, u_plane, v_plane) = (planes[0], planes[1], planes[2]);
+
+    for y in 0..height {
+        for x in 0..width {
+            let chroma_index = (y / 2) * u_plane.width + x / 2;
+            let (r, g, b) = yuv_to_rgb(
+                src[luma.offset + y * width + x],
+                src[u_plane.offset + chroma_index],
+                src[v_plane.offset + chroma_index],
+            );
+
+            let offset = (y * width + x) * 4;
+            dst[offset + r_offset] = r;
+            dst[offset + g_offset] = g;
+            dst[offset + b_offset] = b;
+            dst[offset + 3] = 255;
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{FrameError, FrameProcessor, PullableFrameProperties},
+};
+
+use crate::{
+    format::PixelFormat,
+    geometry::{blit, Rect},
+};
+
+/// Extracts a region of frames of a fixed resolution.
+/// Frames whose input buffer is too small are reported as `DropReason::InvalidFrameSize`.
+pub struct FrameCropper<K> {
+    input_key: K,
+    output_key: K,
+
+    input_resolution: (usize, usize),
+    region: Rect,
+
+    format: PixelFormat,
+}
+
+impl<K> FrameCropper<K> {
+    pub fn new(input_key: K, output_key: K, input_resolution: (u32, u32), region: Rect) -> Self {
+        let input_resolution = (input_resolution.0 as usize, input_resolution.1 as usize);
+
+        assert!(
+            region.fits(input_resolution.0, input_resolution.1),
+            "Crop region {:?} exceeds the {:?} input resolution",
+            region,
+            input_resolution
+        );
+
+        Self {
+            input_key,
+            output_key,
+            input_resolution,
+            region,
+            format: PixelFormat::Rgba,
+        }
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn output_resolution(&self) -> (u32, u32) {
+        (self.region.width as u32, self.region.height as u32)
+    }
+
+    fn crop(&self, input: &[u8], output: &mut BytesMut) {
+        let (input_width, input_height) = self.input_resolution;
+
+        output.clear();
+        output.resize(
+            self.format
+                .buffer_size(self.region.width, self.region.height),
+            0,
+        );
+
+        let src_planes = self.format.planes(input_width, input_height);
+        let dst_planes = self.format.planes(self.region.width, self.region.height);
+
+        for (src, dst) in src_planes.iter().zip(dst_planes.iter()) {
+            let (horizontal, vertical) = src.subsampling;
+            let source_rect = self.region.subsampled(horizontal, vertical);
+
+            blit(input, src, source_rect, output, dst, (0, 0));
+        }
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for FrameCropper<K>
+where
+    K: Copy + Send,
+    F: PullableFrameProperties<K, BytesMut> + FrameError<DropReason> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        debug!("Cropping {:?} frame to {:?}...", self.format, self.region);
+
+        let input = frame_data.pull(&self.input_key).unwrap();
+
+        let (input_width, input_height) = self.input_resolution;
+        if self.format.holds_frame(&input, input_width, input_height) {
+            let mut output = frame_data.pull(&self.output_key).unwrap();
+            self.crop(&input, &mut output);
+            frame_data.push(self.output_key, output);
+        } else {
+            frame_data.report_error(DropReason::InvalidFrameSize);
+        }
+
+        frame_data.push(self.input_key, input);
+
+        Some(frame_data)
+    }
+}
@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{BorrowFrameProperties, FrameError, FrameProcessor, OptionalFrameData},
+};
+
+use crate::{
+    format::{PixelFormat, Plane},
+    geometry::Rect,
+};
+
+/// Regions of a frame that changed since the previous one, in pixel coordinates
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirtyRegions {
+    rects: Vec<Rect>,
+}
+
+impl DirtyRegions {
+    pub fn new(rects: Vec<Rect>) -> Self {
+        Self { rects }
+    }
+
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Number of dirty pixels
+    pub fn area(&self) -> usize {
+        self.rects.iter().map(Rect::area).sum()
+    }
+}
+
+/// Compares each frame to the previous one in square tiles, publishing the changed ones as
+/// `DirtyRegions`. Horizontally adjacent dirty tiles are merged into a single rectangle.
+///
+/// The first frame, and any frame following a buffer too small for the resolution, is entirely
+/// dirty.
+pub struct DamageDetector<K> {
+    buffer_key: K,
+
+    resolution: (usize, usize),
+    format: PixelFormat,
+    tile_size: usize,
+
+    drop_unchanged: bool,
+
+    previous_frame: Vec<u8>,
+}
+
+impl<K> DamageDetector<K> {
+    pub fn new(buffer_key: K, resolution: (u32, u32)) -> Self {
+        Self {
+            buffer_key,
+            resolution: (resolution.0 as usize, resolution.1 as usize),
+            format: PixelFormat::Rgba,
+            tile_size: 64,
+            drop_unchanged: false,
+            previous_frame: Vec::new(),
+        }
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the side of the compared tiles, in pixels (64 by default)
+    pub fn tile_size(mut self, tile_size: usize) -> Self {
+        assert!(tile_size > 0, "Tile size must be positive");
+        self.tile_size = tile_size;
+        self
+    }
+
+    /// Reports frames identical to the previous one with `DropReason::UnchangedFrame`
+    pub fn drop_unchanged(mut self) -> Self {
+        self.drop_unchanged = true;
+        self
+    }
+
+    fn full_frame(&self) -> Rect {
+        Rect::new(0, 0, self.resolution.0, self.resolution.1)
+    }
+
+    fn detect(&self, buffer: &[u8]) -> Vec<Rect> {
+        let (width, height) = self.resolution;
+        let frame_size = self.format.buffer_size(width, height);
+
+        if buffer.len() < frame_size || self.previous_frame.len() < frame_size {
+            return vec![self.full_frame()];
+        }
+
+        let planes = self.format.planes(width, height);
+        let mut rects: Vec<Rect> = Vec::new();
+
+        for tile_y in (0..height).step_by(self.tile_size) {
+            let mut run: Option<Rect> = None;
+
+            for tile_x in (0..width).step_by(self.tile_size) {
+                let tile = Rect::new(
+                    tile_x,
+                    tile_y,
+                    self.tile_size.min(width - tile_x),
+                    self.tile_size.min(height - tile_y),
+                );
+
+                let changed = planes
+                    .iter()
+                    .any(|plane| tile_changed(plane, &tile, &self.previous_frame, buffer));
+
+                match (changed, run.as_mut()) {
+                    (true, Some(run)) => run.width += tile.width,
+                    (true, None) => run = Some(tile),
+                    (false, _) => rects.extend(run.take()),
+                }
+            }
+
+            rects.extend(run);
+        }
+
+        rects
+    }
+}
+
+fn tile_changed(plane: &Plane, tile: &Rect, previous: &[u8], current: &[u8]) -> bool {
+    let (horizontal, vertical) = plane.subsampling;
+    let tile = tile.subsampled(horizontal, vertical);
+    let row_size = tile.width.min(plane.width.saturating_sub(tile.x)) * plane.bytes_per_pixel;
+
+    (tile.y..tile.bottom().min(plane.height)).any(|y| {
+        let start = plane.offset + y * plane.stride() + tile.x * plane.bytes_per_pixel;
+        previous[start..start + row_size] != current[start..start + row_size]
+    })
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for DamageDetector<K>
+where
+    K: Send,
+    F: BorrowFrameProperties<K, BytesMut>
+        + OptionalFrameData<DirtyRegions>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+        let rects = self.detect(buffer);
+
+        self.previous_frame.clear();
+        self.previous_frame.extend_from_slice(buffer);
+
+        let dirty_regions = DirtyRegions::new(rects);
+        debug!(
+            "Detected {} dirty regions ({} pixels)",
+            dirty_regions.rects().len(),
+            dirty_regions.area()
+        );
+
+        if self.drop_unchanged && dirty_regions.is_empty() {
+            frame_data.report_error(DropReason::UnchangedFrame);
+        }
+
+        *frame_data.find_mut() = Some(dirty_regions);
+
+        Some(frame_data)
+    }
+}
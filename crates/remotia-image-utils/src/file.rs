@@ -0,0 +1,259 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{convert::convert_frame, format::PixelFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFileFormat {
+    Png,
+
+    /// Binary portable pixmap (P6), or graymap (P5) when reading
+    Ppm,
+
+    /// Buffer bytes as they are, without any header
+    Raw,
+}
+
+impl ImageFileFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "ppm" | "pgm" | "pnm" => Some(Self::Ppm),
+            "raw" | "rgba" | "bgra" | "yuv" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Ppm => "ppm",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+/// Decoded image, with pixels stored as packed RGBA
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Converts the image to the given pixel format, appending the result to `output`
+    pub fn write_as(&self, format: PixelFormat, output: &mut Vec<u8>) {
+        let start = output.len();
+        output.resize(start + format.buffer_size(self.width, self.height), 0);
+        convert_frame(
+            &self.pixels,
+            PixelFormat::Rgba,
+            &mut output[start..],
+            format,
+            (self.width, self.height),
+        );
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads a PNG or PPM image. Raw files carry no header and should be read directly.
+pub fn read_image<P: AsRef<Path>>(path: P) -> io::Result<RgbaImage> {
+    let path = path.as_ref();
+    match ImageFileFormat::from_path(path) {
+        Some(ImageFileFormat::Png) => read_png(BufReader::new(File::open(path)?)),
+        Some(ImageFileFormat::Ppm) => read_ppm(BufReader::new(File::open(path)?)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Unable to decode {:?}, unsupported image format", path),
+        )),
+    }
+}
+
+pub fn read_png<R: Read>(reader: R) -> io::Result<RgbaImage> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+
+    let (width, height) = (info.width as usize, info.height as usize);
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer
+            .iter()
+            .flat_map(|value| [*value, *value, *value, 255])
+            .collect(),
+        png::ColorType::Indexed => {
+            return Err(invalid_data("Unexpected indexed PNG output".to_string()))
+        }
+    };
+
+    Ok(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn read_ppm_token<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut token = String::new();
+    let mut byte = [0u8];
+
+    loop {
+        reader.read_exact(&mut byte)?;
+        match byte[0] {
+            b'#' => {
+                let mut comment = Vec::new();
+                reader.read_until(b'\n', &mut comment)?;
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            }
+            value if value.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            }
+            value => token.push(value as char),
+        }
+    }
+}
+
+fn read_ppm_number<R: BufRead>(reader: &mut R) -> io::Result<usize> {
+    let token = read_ppm_token(reader)?;
+    token
+        .parse()
+        .map_err(|_| invalid_data(format!("Invalid PPM header value '{}'", token)))
+}
+
+pub fn read_ppm<R: BufRead>(mut reader: R) -> io::Result<RgbaImage> {
+    let magic = read_ppm_token(&mut reader)?;
+    let channels = match magic.as_str() {
+        "P6" => 3,
+        "P5" => 1,
+        _ => return Err(invalid_data(format!("Unsupported PPM type '{}'", magic))),
+    };
+
+    let width = read_ppm_number(&mut reader)?;
+    let height = read_ppm_number(&mut reader)?;
+    let max_value = read_ppm_number(&mut reader)?;
+
+    if max_value == 0 || max_value > 255 {
+        return Err(invalid_data(format!(
+            "Unsupported PPM maximum value {}",
+            max_value
+        )));
+    }
+
+    let samples_count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(channels))
+        .ok_or_else(|| invalid_data(format!("Invalid PPM size {}x{}", width, height)))?;
+
+    // The samples are read up to the size announced by the header, so that a corrupted header
+    // does not allocate more than the data actually contains
+    let mut samples = Vec::new();
+    reader
+        .take(samples_count as u64)
+        .read_to_end(&mut samples)?;
+    if samples.len() < samples_count {
+        return Err(invalid_data(format!(
+            "Truncated PPM data: {} of {} bytes for a {}x{} image",
+            samples.len(),
+            samples_count,
+            width,
+            height
+        )));
+    }
+
+    let scale = |value: u8| (value as usize * 255 / max_value) as u8;
+    let pixels = if channels == 3 {
+        samples
+            .chunks_exact(3)
+            .flat_map(|pixel| [scale(pixel[0]), scale(pixel[1]), scale(pixel[2]), 255])
+            .collect()
+    } else {
+        samples
+            .iter()
+            .map(|value| scale(*value))
+            .flat_map(|value| [value, value, value, 255])
+            .collect()
+    };
+
+    Ok(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Writes a frame to an image file, converting it from its pixel format when needed
+pub fn write_image<P: AsRef<Path>>(
+    path: P,
+    file_format: ImageFileFormat,
+    buffer: &[u8],
+    format: PixelFormat,
+    resolution: (usize, usize),
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    if file_format == ImageFileFormat::Raw {
+        writer.write_all(&buffer[..format.buffer_size(resolution.0, resolution.1)])?;
+        return writer.flush();
+    }
+
+    let mut rgba = vec![0; PixelFormat::Rgba.buffer_size(resolution.0, resolution.1)];
+    convert_frame(buffer, format, &mut rgba, PixelFormat::Rgba, resolution);
+
+    match file_format {
+        ImageFileFormat::Png => write_png(&mut writer, &rgba, resolution)?,
+        _ => write_ppm(&mut writer, &rgba, resolution)?,
+    }
+
+    writer.flush()
+}
+
+pub fn write_png<W: Write>(writer: W, rgba: &[u8], resolution: (usize, usize)) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, resolution.0 as u32, resolution.1 as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+pub fn write_ppm<W: Write>(
+    mut writer: W,
+    rgba: &[u8],
+    resolution: (usize, usize),
+) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", resolution.0, resolution.1)?;
+
+    let samples: Vec<u8> = rgba
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+
+    writer.write_all(&samples)
+}
@@ -0,0 +1,106 @@
+use log::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Packed 8-bit RGBA, 4 bytes per pixel
+    Rgba,
+
+    /// Packed 8-bit BGRA, 4 bytes per pixel (as produced by scrap)
+    Bgra,
+
+    /// Planar 8-bit YUV with 2x2 chroma subsampling (Y, then U, then V)
+    Yuv420p,
+}
+
+/// Layout of a single plane inside a frame buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plane {
+    pub offset: usize,
+    pub width: usize,
+    pub height: usize,
+    pub bytes_per_pixel: usize,
+
+    /// Horizontal and vertical subsampling factors relative to the frame resolution
+    pub subsampling: (usize, usize),
+}
+
+impl Plane {
+    pub fn stride(&self) -> usize {
+        self.width * self.bytes_per_pixel
+    }
+
+    pub fn size(&self) -> usize {
+        self.stride() * self.height
+    }
+
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.size()
+    }
+}
+
+impl PixelFormat {
+    pub fn is_planar(&self) -> bool {
+        matches!(self, Self::Yuv420p)
+    }
+
+    pub fn planes(&self, width: usize, height: usize) -> Vec<Plane> {
+        match self {
+            Self::Rgba | Self::Bgra => vec![Plane {
+                offset: 0,
+                width,
+                height,
+                bytes_per_pixel: 4,
+                subsampling: (1, 1),
+            }],
+            Self::Yuv420p => {
+                let luma = Plane {
+                    offset: 0,
+                    width,
+                    height,
+                    bytes_per_pixel: 1,
+                    subsampling: (1, 1),
+                };
+
+                let chroma_width = width.div_ceil(2);
+                let chroma_height = height.div_ceil(2);
+
+                let u = Plane {
+                    offset: luma.size(),
+                    width: chroma_width,
+                    height: chroma_height,
+                    bytes_per_pixel: 1,
+                    subsampling: (2, 2),
+                };
+
+                let v = Plane {
+                    offset: u.offset + u.size(),
+                    ..u
+                };
+
+                vec![luma, u, v]
+            }
+        }
+    }
+
+    pub fn buffer_size(&self, width: usize, height: usize) -> usize {
+        self.planes(width, height).iter().map(Plane::size).sum()
+    }
+
+    /// Whether `buffer` holds a whole `width`x`height` frame, warning about it otherwise
+    pub(crate) fn holds_frame(&self, buffer: &[u8], width: usize, height: usize) -> bool {
+        let frame_size = self.buffer_size(width, height);
+        if buffer.len() < frame_size {
+            warn!(
+                "Input buffer is too small for a {}x{} {:?} frame ({} < {})",
+                width,
+                height,
+                self,
+                buffer.len(),
+                frame_size
+            );
+            return false;
+        }
+
+        true
+    }
+}
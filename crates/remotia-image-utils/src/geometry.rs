@@ -0,0 +1,98 @@
+use crate::format::Plane;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> usize {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> usize {
+        self.y + self.height
+    }
+
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn fits(&self, width: usize, height: usize) -> bool {
+        self.right() <= width && self.bottom() <= height
+    }
+
+    /// Maps the rect onto a plane whose resolution is scaled down by the given factors,
+    /// as in the chroma planes of subsampled formats
+    pub fn subsampled(&self, horizontal: usize, vertical: usize) -> Self {
+        Self {
+            x: self.x / horizontal,
+            y: self.y / vertical,
+            width: self.width.div_ceil(horizontal),
+            height: self.height.div_ceil(vertical),
+        }
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= x || bottom <= y {
+            return None;
+        }
+
+        Some(Rect::new(x, y, right - x, bottom - y))
+    }
+}
+
+/// Copies the `source_rect` area of a plane into another plane at the given position.
+/// Both the source and the destination areas are clipped to their plane bounds.
+pub(crate) fn blit(
+    src_buffer: &[u8],
+    src: &Plane,
+    source_rect: Rect,
+    dst_buffer: &mut [u8],
+    dst: &Plane,
+    position: (usize, usize),
+) {
+    let source_rect = match source_rect.intersection(&Rect::new(0, 0, src.width, src.height)) {
+        Some(rect) => rect,
+        None => return,
+    };
+
+    let (dst_x, dst_y) = position;
+    if dst_x >= dst.width || dst_y >= dst.height {
+        return;
+    }
+
+    let width = source_rect.width.min(dst.width - dst_x);
+    let height = source_rect.height.min(dst.height - dst_y);
+    let row_size = width * src.bytes_per_pixel;
+
+    for row in 0..height {
+        let src_start =
+            src.offset + (source_rect.y + row) * src.stride() + source_rect.x * src.bytes_per_pixel;
+        let dst_start = dst.offset + (dst_y + row) * dst.stride() + dst_x * dst.bytes_per_pixel;
+
+        dst_buffer[dst_start..dst_start + row_size]
+            .copy_from_slice(&src_buffer[src_start..src_start + row_size]);
+    }
+}
@@ -0,0 +1,14 @@
+pub mod color;
+pub mod convert;
+pub mod file;
+pub mod format;
+pub mod geometry;
+pub mod y4m;
+
+pub mod crop;
+pub mod damage;
+pub mod pad;
+pub mod scale;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{FrameError, FrameProcessor, PullableFrameProperties},
+};
+
+use crate::{
+    color::plane_pixels,
+    format::PixelFormat,
+    geometry::{blit, Rect},
+};
+
+/// Places the input frame on a larger canvas filled with a solid color.
+/// The input is centered on the canvas unless a position is specified.
+/// Frames whose input buffer is too small are reported as `DropReason::InvalidFrameSize`.
+pub struct FramePadder<K> {
+    input_key: K,
+    output_key: K,
+
+    input_resolution: (usize, usize),
+    output_resolution: (usize, usize),
+    position: Option<(usize, usize)>,
+
+    format: PixelFormat,
+    fill: [u8; 4],
+}
+
+impl<K> FramePadder<K> {
+    pub fn new(
+        input_key: K,
+        output_key: K,
+        input_resolution: (u32, u32),
+        output_resolution: (u32, u32),
+    ) -> Self {
+        Self {
+            input_key,
+            output_key,
+            input_resolution: (input_resolution.0 as usize, input_resolution.1 as usize),
+            output_resolution: (output_resolution.0 as usize, output_resolution.1 as usize),
+            position: None,
+            format: PixelFormat::Rgba,
+            fill: [0, 0, 0, 255],
+        }
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn position(mut self, x: u32, y: u32) -> Self {
+        self.position = Some((x as usize, y as usize));
+        self
+    }
+
+    /// Sets the RGBA color of the padding area
+    pub fn fill(mut self, rgba: [u8; 4]) -> Self {
+        self.fill = rgba;
+        self
+    }
+
+    fn placement(&self) -> (usize, usize) {
+        self.position.unwrap_or_else(|| {
+            let (input_width, input_height) = self.input_resolution;
+            let (output_width, output_height) = self.output_resolution;
+
+            (
+                output_width.saturating_sub(input_width) / 2,
+                output_height.saturating_sub(input_height) / 2,
+            )
+        })
+    }
+
+    fn pad(&self, input: &[u8], output: &mut BytesMut) {
+        let (input_width, input_height) = self.input_resolution;
+        let (output_width, output_height) = self.output_resolution;
+        let (x, y) = self.placement();
+
+        output.clear();
+        output.resize(self.format.buffer_size(output_width, output_height), 0);
+
+        let src_planes = self.format.planes(input_width, input_height);
+        let dst_planes = self.format.planes(output_width, output_height);
+        let fill_pixels = plane_pixels(self.format, self.fill);
+
+        for ((src, dst), fill_pixel) in src_planes
+            .iter()
+            .zip(dst_planes.iter())
+            .zip(fill_pixels.iter())
+        {
+            output[dst.range()]
+                .chunks_exact_mut(dst.bytes_per_pixel)
+                .for_each(|pixel| pixel.copy_from_slice(fill_pixel));
+
+            let (horizontal, vertical) = dst.subsampling;
+
+            blit(
+                input,
+                src,
+                Rect::new(0, 0, src.width, src.height),
+                output,
+                dst,
+                (x / horizontal, y / vertical),
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for FramePadder<K>
+where
+    K: Copy + Send,
+    F: PullableFrameProperties<K, BytesMut> + FrameError<DropReason> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        debug!(
+            "Padding {:?} frame from {:?} to {:?}...",
+            self.format, self.input_resolution, self.output_resolution
+        );
+
+        let input = frame_data.pull(&self.input_key).unwrap();
+
+        let (input_width, input_height) = self.input_resolution;
+        if self.format.holds_frame(&input, input_width, input_height) {
+            let mut output = frame_data.pull(&self.output_key).unwrap();
+            self.pad(&input, &mut output);
+            frame_data.push(self.output_key, output);
+        } else {
+            frame_data.report_error(DropReason::InvalidFrameSize);
+        }
+
+        frame_data.push(self.input_key, input);
+
+        Some(frame_data)
+    }
+}
@@ -0,0 +1,233 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{FrameError, FrameProcessor, PullableFrameProperties},
+};
+
+use crate::format::{PixelFormat, Plane};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingFilter {
+    Nearest,
+    #[default]
+    Bilinear,
+    /// Box filter averaging all the source pixels covered by a destination pixel,
+    /// best suited for downscaling
+    Area,
+}
+
+/// Scales frames of a fixed resolution to another one.
+/// Frames whose input buffer is too small are reported as `DropReason::InvalidFrameSize`.
+pub struct FrameScaler<K> {
+    input_key: K,
+    output_key: K,
+
+    input_resolution: (usize, usize),
+    output_resolution: (usize, usize),
+
+    format: PixelFormat,
+    filter: ScalingFilter,
+}
+
+impl<K> FrameScaler<K> {
+    pub fn new(
+        input_key: K,
+        output_key: K,
+        input_resolution: (u32, u32),
+        output_resolution: (u32, u32),
+    ) -> Self {
+        Self {
+            input_key,
+            output_key,
+            input_resolution: (input_resolution.0 as usize, input_resolution.1 as usize),
+            output_resolution: (output_resolution.0 as usize, output_resolution.1 as usize),
+            format: PixelFormat::Rgba,
+            filter: ScalingFilter::default(),
+        }
+    }
+
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn filter(mut self, filter: ScalingFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    fn scale(&self, input: &[u8], output: &mut BytesMut) {
+        let (input_width, input_height) = self.input_resolution;
+        let (output_width, output_height) = self.output_resolution;
+
+        output.clear();
+        output.resize(self.format.buffer_size(output_width, output_height), 0);
+
+        let src_planes = self.format.planes(input_width, input_height);
+        let dst_planes = self.format.planes(output_width, output_height);
+
+        for (src, dst) in src_planes.iter().zip(dst_planes.iter()) {
+            scale_plane(self.filter, input, src, output, dst);
+        }
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for FrameScaler<K>
+where
+    K: Copy + Send,
+    F: PullableFrameProperties<K, BytesMut> + FrameError<DropReason> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        debug!(
+            "Scaling {:?} frame from {:?} to {:?}...",
+            self.format, self.input_resolution, self.output_resolution
+        );
+
+        let input = frame_data.pull(&self.input_key).unwrap();
+
+        let (input_width, input_height) = self.input_resolution;
+        if self.format.holds_frame(&input, input_width, input_height) {
+            let mut output = frame_data.pull(&self.output_key).unwrap();
+            self.scale(&input, &mut output);
+            frame_data.push(self.output_key, output);
+        } else {
+            frame_data.report_error(DropReason::InvalidFrameSize);
+        }
+
+        frame_data.push(self.input_key, input);
+
+        Some(frame_data)
+    }
+}
+
+/// Scales a single plane of `src_buffer` into the corresponding plane of `dst_buffer`.
+/// Both planes must have the same number of bytes per pixel.
+pub fn scale_plane(
+    filter: ScalingFilter,
+    src_buffer: &[u8],
+    src: &Plane,
+    dst_buffer: &mut [u8],
+    dst: &Plane,
+) {
+    assert_eq!(src.bytes_per_pixel, dst.bytes_per_pixel);
+
+    if src.width == 0 || src.height == 0 || dst.width == 0 || dst.height == 0 {
+        return;
+    }
+
+    let src_data = &src_buffer[src.range()];
+    let dst_data = &mut dst_buffer[dst.range()];
+
+    match filter {
+        ScalingFilter::Nearest => scale_nearest(src_data, src, dst_data, dst),
+        ScalingFilter::Bilinear => scale_bilinear(src_data, src, dst_data, dst),
+        ScalingFilter::Area => scale_area(src_data, src, dst_data, dst),
+    }
+}
+
+fn nearest_index(dst_index: usize, src_length: usize, dst_length: usize) -> usize {
+    (((2 * dst_index + 1) * src_length) / (2 * dst_length)).min(src_length - 1)
+}
+
+fn scale_nearest(src_data: &[u8], src: &Plane, dst_data: &mut [u8], dst: &Plane) {
+    let bpp = src.bytes_per_pixel;
+
+    let columns: Vec<usize> = (0..dst.width)
+        .map(|x| nearest_index(x, src.width, dst.width) * bpp)
+        .collect();
+
+    for (y, dst_row) in dst_data.chunks_exact_mut(dst.stride()).enumerate() {
+        let src_y = nearest_index(y, src.height, dst.height);
+        let src_row = &src_data[src_y * src.stride()..(src_y + 1) * src.stride()];
+
+        for (dst_pixel, src_offset) in dst_row.chunks_exact_mut(bpp).zip(columns.iter()) {
+            dst_pixel.copy_from_slice(&src_row[*src_offset..*src_offset + bpp]);
+        }
+    }
+}
+
+/// Returns the two source samples surrounding a destination sample and the weight of the second one
+fn bilinear_taps(dst_index: usize, src_length: usize, dst_length: usize) -> (usize, usize, f32) {
+    let ratio = src_length as f32 / dst_length as f32;
+    let position = ((dst_index as f32 + 0.5) * ratio - 0.5).max(0.0);
+
+    let first = (position as usize).min(src_length - 1);
+    let second = (first + 1).min(src_length - 1);
+
+    (first, second, position - first as f32)
+}
+
+fn scale_bilinear(src_data: &[u8], src: &Plane, dst_data: &mut [u8], dst: &Plane) {
+    let bpp = src.bytes_per_pixel;
+    let stride = src.stride();
+
+    let columns: Vec<(usize, usize, f32)> = (0..dst.width)
+        .map(|x| bilinear_taps(x, src.width, dst.width))
+        .collect();
+
+    for (y, dst_row) in dst_data.chunks_exact_mut(dst.stride()).enumerate() {
+        let (top, bottom, weight_y) = bilinear_taps(y, src.height, dst.height);
+        let top_row = &src_data[top * stride..(top + 1) * stride];
+        let bottom_row = &src_data[bottom * stride..(bottom + 1) * stride];
+
+        for (dst_pixel, (left, right, weight_x)) in
+            dst_row.chunks_exact_mut(bpp).zip(columns.iter())
+        {
+            for (channel, value) in dst_pixel.iter_mut().enumerate() {
+                let sample = |row: &[u8], x: usize| row[x * bpp + channel] as f32;
+
+                let top_value =
+                    sample(top_row, *left) * (1.0 - weight_x) + sample(top_row, *right) * weight_x;
+                let bottom_value = sample(bottom_row, *left) * (1.0 - weight_x)
+                    + sample(bottom_row, *right) * weight_x;
+
+                *value = (top_value * (1.0 - weight_y) + bottom_value * weight_y).round() as u8;
+            }
+        }
+    }
+}
+
+/// Returns the range of source samples covered by a destination sample
+fn area_span(dst_index: usize, src_length: usize, dst_length: usize) -> (usize, usize) {
+    let start = (dst_index * src_length / dst_length).min(src_length - 1);
+    let end = ((dst_index + 1) * src_length)
+        .div_ceil(dst_length)
+        .clamp(start + 1, src_length);
+
+    (start, end)
+}
+
+fn scale_area(src_data: &[u8], src: &Plane, dst_data: &mut [u8], dst: &Plane) {
+    let bpp = src.bytes_per_pixel;
+    let stride = src.stride();
+
+    let columns: Vec<(usize, usize)> = (0..dst.width)
+        .map(|x| area_span(x, src.width, dst.width))
+        .collect();
+
+    let mut sums = vec![0u32; bpp];
+
+    for (y, dst_row) in dst_data.chunks_exact_mut(dst.stride()).enumerate() {
+        let (top, bottom) = area_span(y, src.height, dst.height);
+
+        for (dst_pixel, (left, right)) in dst_row.chunks_exact_mut(bpp).zip(columns.iter()) {
+            sums.iter_mut().for_each(|sum| *sum = 0);
+
+            for src_row in src_data[top * stride..bottom * stride].chunks_exact(stride) {
+                for src_pixel in src_row[left * bpp..right * bpp].chunks_exact(bpp) {
+                    for (sum, value) in sums.iter_mut().zip(src_pixel.iter()) {
+                        *sum += *value as u32;
+                    }
+                }
+            }
+
+            let count = ((bottom - top) * (right - left)) as u32;
+            for (value, sum) in dst_pixel.iter_mut().zip(sums.iter()) {
+                *value = ((sum + count / 2) / count) as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{
+        BorrowFrameProperties, FrameError, FrameProcessor, OptionalFrameData,
+        PullableFrameProperties,
+    },
+};
+
+use crate::{
+    convert::convert_frame,
+    crop::FrameCropper,
+    damage::{DamageDetector, DirtyRegions},
+    file::{read_image, read_ppm, write_image, ImageFileFormat},
+    format::PixelFormat,
+    geometry::Rect,
+    pad::FramePadder,
+    scale::{FrameScaler, ScalingFilter},
+};
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+enum BufferType {
+    Input,
+    Output,
+}
+
+#[derive(Default)]
+struct TestFrameData {
+    buffers: HashMap<BufferType, BytesMut>,
+    dirty_regions: Option<DirtyRegions>,
+    error: Option<DropReason>,
+}
+
+impl BorrowFrameProperties<BufferType, BytesMut> for TestFrameData {
+    fn get_ref(&self, key: &BufferType) -> Option<&BytesMut> {
+        self.buffers.get(key)
+    }
+}
+
+impl OptionalFrameData<DirtyRegions> for TestFrameData {
+    fn find_mut(&mut self) -> &mut Option<DirtyRegions> {
+        &mut self.dirty_regions
+    }
+
+    fn find(&self) -> &Option<DirtyRegions> {
+        &self.dirty_regions
+    }
+}
+
+impl FrameError<DropReason> for TestFrameData {
+    fn report_error(&mut self, error: DropReason) {
+        self.error = Some(error);
+    }
+
+    fn get_error(&self) -> Option<DropReason> {
+        self.error
+    }
+}
+
+impl PullableFrameProperties<BufferType, BytesMut> for TestFrameData {
+    fn push(&mut self, key: BufferType, value: BytesMut) {
+        self.buffers.insert(key, value);
+    }
+
+    fn pull(&mut self, key: &BufferType) -> Option<BytesMut> {
+        self.buffers.remove(key)
+    }
+}
+
+fn frame_with_input(input: &[u8]) -> TestFrameData {
+    let mut frame_data = TestFrameData::default();
+    frame_data.push(BufferType::Input, BytesMut::from(input));
+    frame_data.push(BufferType::Output, BytesMut::new());
+    frame_data
+}
+
+#[tokio::test]
+async fn test_nearest_upscaling() {
+    let mut scaler = FrameScaler::new(BufferType::Input, BufferType::Output, (2, 1), (4, 2))
+        .format(PixelFormat::Rgba)
+        .filter(ScalingFilter::Nearest);
+
+    let input = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut frame_data = scaler.process(frame_with_input(&input)).await.unwrap();
+    let output = frame_data.pull(&BufferType::Output).unwrap();
+
+    let expected_row = [1, 2, 3, 4, 1, 2, 3, 4, 5, 6, 7, 8, 5, 6, 7, 8];
+    assert_eq!(&output[..16], &expected_row);
+    assert_eq!(&output[16..], &expected_row);
+}
+
+#[tokio::test]
+async fn test_area_downscaling_yuv() {
+    let mut scaler = FrameScaler::new(BufferType::Input, BufferType::Output, (4, 4), (2, 2))
+        .format(PixelFormat::Yuv420p)
+        .filter(ScalingFilter::Area);
+
+    let mut input = vec![0u8; PixelFormat::Yuv420p.buffer_size(4, 4)];
+    input[..16].copy_from_slice(&[
+        10, 20, 0, 0, //
+        30, 40, 0, 0, //
+        0, 0, 100, 100, //
+        0, 0, 100, 100,
+    ]);
+    input[16..].fill(128);
+
+    let mut frame_data = scaler.process(frame_with_input(&input)).await.unwrap();
+    let output = frame_data.pull(&BufferType::Output).unwrap();
+
+    assert_eq!(output.len(), PixelFormat::Yuv420p.buffer_size(2, 2));
+    assert_eq!(&output[..4], &[25, 0, 0, 100]);
+    assert_eq!(&output[4..], &[128, 128]);
+}
+
+#[tokio::test]
+async fn test_bilinear_keeps_flat_color() {
+    let mut scaler = FrameScaler::new(BufferType::Input, BufferType::Output, (3, 3), (7, 5))
+        .format(PixelFormat::Bgra)
+        .filter(ScalingFilter::Bilinear);
+
+    let input = [50, 100, 150, 255].repeat(9);
+    let mut frame_data = scaler.process(frame_with_input(&input)).await.unwrap();
+    let output = frame_data.pull(&BufferType::Output).unwrap();
+
+    assert_eq!(&output[..], &[50, 100, 150, 255].repeat(35)[..]);
+}
+
+#[tokio::test]
+async fn test_crop_yuv() {
+    let mut cropper = FrameCropper::new(
+        BufferType::Input,
+        BufferType::Output,
+        (4, 2),
+        Rect::new(2, 0, 2, 2),
+    )
+    .format(PixelFormat::Yuv420p);
+
+    let input = [
+        0, 1, 2, 3, //
+        4, 5, 6, 7, //
+        10, 11, // U
+        20, 21, // V
+    ];
+
+    let mut frame_data = cropper.process(frame_with_input(&input)).await.unwrap();
+    let output = frame_data.pull(&BufferType::Output).unwrap();
+
+    assert_eq!(&output[..], &[2, 3, 6, 7, 11, 21]);
+}
+
+#[tokio::test]
+async fn test_short_input() {
+    let mut cropper = FrameCropper::new(
+        BufferType::Input,
+        BufferType::Output,
+        (4, 2),
+        Rect::new(2, 0, 2, 2),
+    );
+    let frame_data = cropper.process(frame_with_input(&[0; 16])).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::InvalidFrameSize));
+    assert_eq!(frame_data.get_ref(&BufferType::Input).unwrap().len(), 16);
+
+    let mut padder = FramePadder::new(BufferType::Input, BufferType::Output, (2, 2), (4, 4));
+    let frame_data = padder.process(frame_with_input(&[0; 12])).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::InvalidFrameSize));
+
+    let mut scaler = FrameScaler::new(BufferType::Input, BufferType::Output, (2, 2), (4, 4));
+    let frame_data = scaler.process(frame_with_input(&[0; 12])).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::InvalidFrameSize));
+}
+
+#[tokio::test]
+async fn test_pad_rgba() {
+    let mut padder = FramePadder::new(BufferType::Input, BufferType::Output, (1, 1), (3, 1))
+        .format(PixelFormat::Rgba)
+        .fill([9, 9, 9, 255]);
+
+    let input = [1, 2, 3, 4];
+    let mut frame_data = padder.process(frame_with_input(&input)).await.unwrap();
+    let output = frame_data.pull(&BufferType::Output).unwrap();
+
+    assert_eq!(&output[..], &[9, 9, 9, 255, 1, 2, 3, 4, 9, 9, 9, 255]);
+}
+
+#[tokio::test]
+async fn test_damage_detection() {
+    let mut detector = DamageDetector::new(BufferType::Input, (8, 4))
+        .format(PixelFormat::Yuv420p)
+        .tile_size(2)
+        .drop_unchanged();
+
+    let mut input = vec![0u8; PixelFormat::Yuv420p.buffer_size(8, 4)];
+    let frame_data = detector.process(frame_with_input(&input)).await.unwrap();
+    assert_eq!(
+        frame_data.find().as_ref().unwrap().rects(),
+        &[Rect::new(0, 0, 8, 4)]
+    );
+
+    let frame_data = detector.process(frame_with_input(&input)).await.unwrap();
+    assert!(frame_data.find().as_ref().unwrap().is_empty());
+    assert_eq!(frame_data.get_error(), Some(DropReason::UnchangedFrame));
+
+    // Two adjacent luma tiles of the first row, and a chroma sample of the last tile row
+    input[2] = 1;
+    input[4] = 1;
+    input[32 + 4 + 3] = 1;
+    let frame_data = detector.process(frame_with_input(&input)).await.unwrap();
+    assert_eq!(
+        frame_data.find().as_ref().unwrap().rects(),
+        &[Rect::new(2, 0, 4, 2), Rect::new(6, 2, 2, 2)]
+    );
+    assert_eq!(frame_data.get_error(), None);
+}
+
+#[test]
+fn test_rgba_yuv_roundtrip() {
+    let input = [
+        [255, 255, 255, 255],
+        [0, 0, 0, 255],
+        [255, 0, 0, 255],
+        [0, 0, 255, 255],
+    ]
+    .concat();
+
+    let mut yuv = vec![0; PixelFormat::Yuv420p.buffer_size(2, 2)];
+    convert_frame(
+        &input,
+        PixelFormat::Rgba,
+        &mut yuv,
+        PixelFormat::Yuv420p,
+        (2, 2),
+    );
+    assert_eq!(&yuv[..4], &[235, 16, 82, 41]);
+
+    let mut bgra = vec![0; PixelFormat::Bgra.buffer_size(2, 2)];
+    convert_frame(
+        &yuv,
+        PixelFormat::Yuv420p,
+        &mut bgra,
+        PixelFormat::Bgra,
+        (2, 2),
+    );
+    assert_eq!(bgra.len(), 16);
+    assert!(bgra.chunks_exact(4).all(|pixel| pixel[3] == 255));
+}
+
+#[test]
+fn test_image_files_roundtrip() {
+    let folder = std::env::temp_dir().join(format!("remotia-image-utils-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+
+    let bgra = [[10, 20, 30, 255], [40, 50, 60, 255], [70, 80, 90, 255]].concat();
+
+    for file_format in [ImageFileFormat::Png, ImageFileFormat::Ppm] {
+        let path = folder.join(format!("frame.{}", file_format.extension()));
+        write_image(&path, file_format, &bgra, PixelFormat::Bgra, (3, 1)).unwrap();
+
+        let image = read_image(&path).unwrap();
+        assert_eq!((image.width, image.height), (3, 1));
+        assert_eq!(
+            image.pixels,
+            [[30, 20, 10, 255], [60, 50, 40, 255], [90, 80, 70, 255]].concat()
+        );
+    }
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn test_ppm_truncated_data() {
+    let mut ppm = b"P6\n# comment\n2 2\n255\n".to_vec();
+    ppm.extend_from_slice(&[255; 6]);
+
+    let error = read_ppm(&ppm[..]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    // A huge announced size is rejected without allocating it
+    let error = read_ppm(&b"P6 100000 100000 255\n\x00"[..]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    ppm.extend_from_slice(&[0; 6]);
+    let image = read_ppm(&ppm[..]).unwrap();
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(
+        &image.pixels[..8],
+        &[255, 255, 255, 255, 255, 255, 255, 255]
+    );
+    assert_eq!(&image.pixels[8..12], &[0, 0, 0, 255]);
+}
@@ -0,0 +1,30 @@
+use y4m::Colorspace;
+
+use crate::format::PixelFormat;
+
+/// Size in bytes of the Y, U and V planes of a frame of the given colorspace
+pub fn plane_sizes(colorspace: Colorspace, width: usize, height: usize) -> (usize, usize, usize) {
+    let bytes_per_sample = colorspace.get_bytes_per_sample();
+
+    let y_size = width * height * bytes_per_sample;
+    let chroma_size = match colorspace {
+        Colorspace::Cmono | Colorspace::Cmono12 => 0,
+        Colorspace::C422 | Colorspace::C422p10 | Colorspace::C422p12 => {
+            width.div_ceil(2) * height * bytes_per_sample
+        }
+        Colorspace::C444 | Colorspace::C444p10 | Colorspace::C444p12 => y_size,
+        _ => width.div_ceil(2) * height.div_ceil(2) * bytes_per_sample,
+    };
+
+    (y_size, chroma_size, chroma_size)
+}
+
+/// Pixel format of the frames of the given colorspace, if supported by the conversions
+pub fn pixel_format(colorspace: Colorspace) -> Option<PixelFormat> {
+    match colorspace {
+        Colorspace::C420 | Colorspace::C420jpeg | Colorspace::C420paldv | Colorspace::C420mpeg2 => {
+            Some(PixelFormat::Yuv420p)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{
+        BorrowFrameProperties, BorrowMutFrameProperties, FrameError, FrameProcessor,
+        OptionalFrameData,
+    },
+};
+
+use crate::events::InputEvent;
+
+fn encode_event(event: &InputEvent) -> Vec<u8> {
+    bincode::serde::encode_to_vec(event, bincode::config::standard()).unwrap()
+}
+
+/// Decodes an event, rejecting the buffers containing anything else after it
+fn decode_event(buffer: &[u8]) -> Option<InputEvent> {
+    match bincode::serde::decode_from_slice(buffer, bincode::config::standard()) {
+        Ok((event, read_bytes)) if read_bytes == buffer.len() => Some(event),
+        _ => None,
+    }
+}
+
+/// Writes the input event of the frame to a buffer, ready to be sent by any of the transmission
+/// processors. Frames without an event are reported as `DropReason::EmptyFrame`.
+pub struct InputEventEncoder<K> {
+    buffer_key: K,
+}
+
+impl<K> InputEventEncoder<K> {
+    pub fn new(buffer_key: K) -> Self {
+        Self { buffer_key }
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for InputEventEncoder<K>
+where
+    K: Send,
+    F: OptionalFrameData<InputEvent>
+        + BorrowMutFrameProperties<K, BytesMut>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let event = match *frame_data.find() {
+            Some(event) => event,
+            None => {
+                debug!("No input event to encode");
+                frame_data.report_error(DropReason::EmptyFrame);
+                return Some(frame_data);
+            }
+        };
+
+        let buffer = frame_data.get_mut_ref(&self.buffer_key).unwrap();
+        buffer.clear();
+        buffer.extend_from_slice(&encode_event(&event));
+
+        Some(frame_data)
+    }
+}
+
+/// Reads an input event from a buffer, reporting `DropReason::InvalidPacket` if it is malformed
+pub struct InputEventDecoder<K> {
+    buffer_key: K,
+}
+
+impl<K> InputEventDecoder<K> {
+    pub fn new(buffer_key: K) -> Self {
+        Self { buffer_key }
+    }
+}
+
+#[async_trait]
+impl<F, K> FrameProcessor<F> for InputEventDecoder<K>
+where
+    K: Send,
+    F: OptionalFrameData<InputEvent>
+        + BorrowFrameProperties<K, BytesMut>
+        + FrameError<DropReason>
+        + Send
+        + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        match decode_event(buffer) {
+            Some(event) => *frame_data.find_mut() = Some(event),
+            None => {
+                debug!("Invalid input event of {} bytes", buffer.len());
+                frame_data.report_error(DropReason::InvalidPacket);
+            }
+        }
+
+        Some(frame_data)
+    }
+}
@@ -0,0 +1,69 @@
+use remotia_core::common::helpers::time::now_timestamp;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+
+    /// Additional buttons, by platform number
+    Other(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InputEventKind {
+    /// Key pressed, identified by its platform scancode
+    KeyDown {
+        key_code: u32,
+    },
+    KeyUp {
+        key_code: u32,
+    },
+
+    /// Absolute pointer position, in pixels of the rendered frame
+    PointerMove {
+        x: f32,
+        y: f32,
+    },
+    PointerButton {
+        button: PointerButton,
+        pressed: bool,
+    },
+
+    /// Scroll amount, in lines
+    PointerScroll {
+        delta_x: f32,
+        delta_y: f32,
+    },
+
+    /// Axis position, between -1.0 and 1.0
+    GamepadAxis {
+        gamepad: u8,
+        axis: u8,
+        value: f32,
+    },
+    GamepadButton {
+        gamepad: u8,
+        button: u8,
+        pressed: bool,
+    },
+}
+
+/// User input event, timestamped when it was generated on the client
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InputEvent {
+    pub timestamp: u128,
+    pub kind: InputEventKind,
+}
+
+impl InputEvent {
+    pub fn new(kind: InputEventKind) -> Self {
+        Self {
+            timestamp: now_timestamp(),
+            kind,
+        }
+    }
+}
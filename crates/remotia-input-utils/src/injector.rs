@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use log::{debug, info};
+use remotia_core::{
+    common::helpers::time::now_timestamp,
+    traits::{FrameProcessor, OptionalFrameData},
+};
+
+use crate::events::InputEvent;
+
+/// Replays input events on the server, e.g. through the OS input APIs or a game engine
+pub trait InputInjector: Send {
+    fn inject(&mut self, event: &InputEvent);
+}
+
+/// Stand-in injector which only logs the received events and their latency
+#[derive(Default)]
+pub struct LoggingInjector {
+    injected_events: usize,
+}
+
+impl LoggingInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn injected_events(&self) -> usize {
+        self.injected_events
+    }
+}
+
+impl InputInjector for LoggingInjector {
+    fn inject(&mut self, event: &InputEvent) {
+        self.injected_events += 1;
+        info!(
+            "Input event #{}: {:?} (latency: {} ms)",
+            self.injected_events,
+            event.kind,
+            now_timestamp().saturating_sub(event.timestamp)
+        );
+    }
+}
+
+/// Hands the input event of each frame to an injector
+pub struct InputEventInjector<I> {
+    injector: I,
+}
+
+impl<I: InputInjector> InputEventInjector<I> {
+    pub fn new(injector: I) -> Self {
+        Self { injector }
+    }
+
+    pub fn injector(&self) -> &I {
+        &self.injector
+    }
+}
+
+#[async_trait]
+impl<F, I> FrameProcessor<F> for InputEventInjector<I>
+where
+    I: InputInjector,
+    F: OptionalFrameData<InputEvent> + Send + 'static,
+{
+    async fn process(&mut self, frame_data: F) -> Option<F> {
+        match frame_data.find() {
+            Some(event) => self.injector.inject(event),
+            None => debug!("No input event to inject"),
+        }
+
+        Some(frame_data)
+    }
+}
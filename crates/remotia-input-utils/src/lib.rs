@@ -0,0 +1,7 @@
+pub mod codec;
+pub mod events;
+pub mod injector;
+pub mod source;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use log::debug;
+use remotia_core::traits::{FrameProcessor, OptionalFrameData};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::events::InputEvent;
+
+/// Waits for the next input event produced by the client (e.g. by a window) and stores it in the
+/// frame. Stops the pipeline once all the senders are dropped.
+pub struct InputEventSource {
+    receiver: UnboundedReceiver<InputEvent>,
+}
+
+impl InputEventSource {
+    pub fn new(receiver: UnboundedReceiver<InputEvent>) -> Self {
+        Self { receiver }
+    }
+
+    /// Creates a source along with the sender to push events into
+    pub fn channel() -> (UnboundedSender<InputEvent>, Self) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (sender, Self::new(receiver))
+    }
+}
+
+#[async_trait]
+impl<F> FrameProcessor<F> for InputEventSource
+where
+    F: OptionalFrameData<InputEvent> + Send + 'static,
+{
+    async fn process(&mut self, mut frame_data: F) -> Option<F> {
+        let event = match self.receiver.recv().await {
+            Some(event) => event,
+            None => {
+                debug!("Input events channel closed");
+                return None;
+            }
+        };
+
+        *frame_data.find_mut() = Some(event);
+
+        Some(frame_data)
+    }
+}
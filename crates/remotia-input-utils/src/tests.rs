@@ -0,0 +1,188 @@
+use std::sync::{Arc, Mutex};
+
+use remotia_buffer_utils::BytesMut;
+use remotia_core::{
+    error::DropReason,
+    traits::{
+        BorrowFrameProperties, BorrowMutFrameProperties, FrameError, FrameProcessor,
+        OptionalFrameData,
+    },
+};
+
+use crate::{
+    codec::{InputEventDecoder, InputEventEncoder},
+    events::{InputEvent, InputEventKind, PointerButton},
+    injector::{InputEventInjector, InputInjector},
+    source::InputEventSource,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct EventBuffer;
+
+#[derive(Default)]
+struct TestFrameData {
+    buffer: BytesMut,
+    event: Option<InputEvent>,
+    error: Option<DropReason>,
+}
+
+impl BorrowFrameProperties<EventBuffer, BytesMut> for TestFrameData {
+    fn get_ref(&self, _: &EventBuffer) -> Option<&BytesMut> {
+        Some(&self.buffer)
+    }
+}
+
+impl BorrowMutFrameProperties<EventBuffer, BytesMut> for TestFrameData {
+    fn get_mut_ref(&mut self, _: &EventBuffer) -> Option<&mut BytesMut> {
+        Some(&mut self.buffer)
+    }
+}
+
+impl OptionalFrameData<InputEvent> for TestFrameData {
+    fn find_mut(&mut self) -> &mut Option<InputEvent> {
+        &mut self.event
+    }
+
+    fn find(&self) -> &Option<InputEvent> {
+        &self.event
+    }
+}
+
+impl FrameError<DropReason> for TestFrameData {
+    fn report_error(&mut self, error: DropReason) {
+        self.error = Some(error);
+    }
+
+    fn get_error(&self) -> Option<DropReason> {
+        self.error
+    }
+}
+
+struct RecordingInjector(Arc<Mutex<Vec<InputEvent>>>);
+
+impl InputInjector for RecordingInjector {
+    fn inject(&mut self, event: &InputEvent) {
+        self.0.lock().unwrap().push(*event);
+    }
+}
+
+fn all_event_kinds() -> Vec<InputEventKind> {
+    vec![
+        InputEventKind::KeyDown { key_code: 30 },
+        InputEventKind::KeyUp { key_code: 30 },
+        InputEventKind::PointerMove { x: 12.5, y: 700.0 },
+        InputEventKind::PointerButton {
+            button: PointerButton::Other(3),
+            pressed: true,
+        },
+        InputEventKind::PointerScroll {
+            delta_x: 0.0,
+            delta_y: -2.0,
+        },
+        InputEventKind::GamepadAxis {
+            gamepad: 1,
+            axis: 2,
+            value: -0.5,
+        },
+        InputEventKind::GamepadButton {
+            gamepad: 0,
+            button: 9,
+            pressed: false,
+        },
+    ]
+}
+
+#[tokio::test]
+async fn test_event_encoding_roundtrip() {
+    let mut encoder = InputEventEncoder::new(EventBuffer);
+    let mut decoder = InputEventDecoder::new(EventBuffer);
+
+    for kind in all_event_kinds() {
+        let event = InputEvent {
+            timestamp: 1_700_000_000_123,
+            kind,
+        };
+
+        let frame_data = TestFrameData {
+            event: Some(event),
+            ..Default::default()
+        };
+        let frame_data = encoder.process(frame_data).await.unwrap();
+
+        let mut received_frame = TestFrameData {
+            buffer: frame_data.buffer.clone(),
+            ..Default::default()
+        };
+        received_frame = decoder.process(received_frame).await.unwrap();
+        assert_eq!(received_frame.event, Some(event));
+        assert_eq!(received_frame.error, None);
+
+        // Truncated or extended buffers are rejected
+        for buffer in [
+            &frame_data.buffer[..frame_data.buffer.len() - 1],
+            &[&frame_data.buffer[..], &[0]].concat()[..],
+        ] {
+            let received_frame = TestFrameData {
+                buffer: BytesMut::from(buffer),
+                ..Default::default()
+            };
+            let received_frame = decoder.process(received_frame).await.unwrap();
+            assert_eq!(received_frame.error, Some(DropReason::InvalidPacket));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_event_pipeline() {
+    let (sender, mut source) = InputEventSource::channel();
+    let mut encoder = InputEventEncoder::new(EventBuffer);
+    let mut decoder = InputEventDecoder::new(EventBuffer);
+
+    let injected_events = Arc::new(Mutex::new(Vec::new()));
+    let mut injector = InputEventInjector::new(RecordingInjector(injected_events.clone()));
+
+    let events: Vec<InputEvent> = all_event_kinds().into_iter().map(InputEvent::new).collect();
+    for event in &events {
+        sender.send(*event).unwrap();
+    }
+    drop(sender);
+
+    while let Some(frame_data) = source.process(TestFrameData::default()).await {
+        let frame_data = encoder.process(frame_data).await.unwrap();
+        assert!(!frame_data.buffer.is_empty());
+
+        // Only the encoded buffer reaches the server
+        let received_frame = TestFrameData {
+            buffer: frame_data.buffer,
+            ..Default::default()
+        };
+
+        let received_frame = decoder.process(received_frame).await.unwrap();
+        injector.process(received_frame).await.unwrap();
+    }
+
+    assert_eq!(*injected_events.lock().unwrap(), events);
+}
+
+#[tokio::test]
+async fn test_invalid_event_is_reported() {
+    let mut decoder = InputEventDecoder::new(EventBuffer);
+
+    let frame_data = TestFrameData {
+        buffer: BytesMut::from(&[0u8; 4][..]),
+        ..Default::default()
+    };
+
+    let frame_data = decoder.process(frame_data).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::InvalidPacket));
+    assert!(frame_data.find().is_none());
+}
+
+#[tokio::test]
+async fn test_missing_event_is_reported() {
+    let mut encoder = InputEventEncoder::new(EventBuffer);
+
+    let frame_data = encoder.process(TestFrameData::default()).await.unwrap();
+    assert_eq!(frame_data.get_error(), Some(DropReason::EmptyFrame));
+    assert!(frame_data.buffer.is_empty());
+}
@@ -0,0 +1,78 @@
+use std::{fs::create_dir_all, path::PathBuf};
+
+use async_trait::async_trait;
+
+use bytes::BytesMut;
+use log::debug;
+use remotia_core::traits::{BorrowFrameProperties, FrameProcessor, FrameProperties};
+use remotia_image_utils::{
+    file::{write_image, ImageFileFormat},
+    format::PixelFormat,
+};
+
+/// Dumps frames as PNG or PPM images named after the value of a frame property,
+/// converting them from their pixel format
+pub struct ImageFrameDumper<K, P> {
+    buffer_key: K,
+    name_key: P,
+
+    resolution: (usize, usize),
+    format: PixelFormat,
+    file_format: ImageFileFormat,
+
+    folder: PathBuf,
+}
+
+impl<K, P> ImageFrameDumper<K, P> {
+    pub fn new(buffer_key: K, name_key: P, folder: PathBuf, resolution: (u32, u32)) -> Self {
+        create_dir_all(folder.clone()).unwrap();
+        Self {
+            buffer_key,
+            name_key,
+            resolution: (resolution.0 as usize, resolution.1 as usize),
+            format: PixelFormat::Rgba,
+            file_format: ImageFileFormat::Png,
+            folder,
+        }
+    }
+
+    /// Pixel format of the dumped buffer
+    pub fn format(mut self, format: PixelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn file_format(mut self, file_format: ImageFileFormat) -> Self {
+        self.file_format = file_format;
+        self
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for ImageFrameDumper<K, P>
+where
+    K: Send,
+    P: Send,
+    F: BorrowFrameProperties<K, BytesMut> + FrameProperties<P, u128> + Send + 'static,
+{
+    async fn process(&mut self, frame_data: F) -> Option<F> {
+        let frame_id = frame_data.get(&self.name_key).unwrap();
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        debug!("Dumping frame {} as {:?}", frame_id, self.file_format);
+
+        let mut file_path = self.folder.clone();
+        file_path.push(format!("{}.{}", frame_id, self.file_format.extension()));
+
+        write_image(
+            &file_path,
+            self.file_format,
+            buffer,
+            self.format,
+            self.resolution,
+        )
+        .unwrap_or_else(|error| panic!("Unable to dump frame to {:?}: {}", file_path, error));
+
+        Some(frame_data)
+    }
+}
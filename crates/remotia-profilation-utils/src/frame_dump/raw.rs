@@ -0,0 +1,60 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use async_trait::async_trait;
+
+use bytes::BytesMut;
+use log::debug;
+use remotia_core::traits::{BorrowFrameProperties, FrameProcessor, FrameProperties};
+
+/// Dumps the bytes of a buffer to a file named after the value of a frame property
+pub struct RawFrameDumper<K, P> {
+    buffer_key: K,
+    name_key: P,
+
+    extension: String,
+
+    folder: PathBuf,
+}
+
+impl<K, P> RawFrameDumper<K, P> {
+    pub fn new(buffer_key: K, name_key: P, folder: PathBuf) -> Self {
+        create_dir_all(folder.clone()).unwrap();
+        Self {
+            buffer_key,
+            name_key,
+            extension: "raw".to_string(),
+            folder,
+        }
+    }
+
+    pub fn extension(mut self, value: &str) -> Self {
+        self.extension = value.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl<F, K, P> FrameProcessor<F> for RawFrameDumper<K, P>
+where
+    K: Send,
+    P: Send,
+    F: BorrowFrameProperties<K, BytesMut> + FrameProperties<P, u128> + Send + 'static,
+{
+    async fn process(&mut self, frame_data: F) -> Option<F> {
+        let frame_id = frame_data.get(&self.name_key).unwrap();
+        let buffer = frame_data.get_ref(&self.buffer_key).unwrap();
+
+        debug!("Dumping frame {}", frame_id);
+
+        let mut file_path = self.folder.clone();
+        file_path.push(format!("{}.{}", frame_id, self.extension));
+        let mut output_file = File::create(file_path.as_path()).unwrap();
+        output_file.write_all(buffer).unwrap();
+
+        Some(frame_data)
+    }
+}
@@ -1,4 +1,5 @@
 pub mod time;
 pub mod frame_drop;
+pub mod frame_dump;
 
 pub mod loggers;
@@ -1,17 +1,29 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 
 use remotia_core::{
-    common::helpers::time::now_timestamp,
+    common::helpers::time::{Clock, SystemClock},
     traits::{FrameProcessor, FrameProperties},
 };
 
 pub struct TimestampAdder<K> {
     id: K,
+    clock: Arc<dyn Clock>,
 }
 
 impl<K> TimestampAdder<K> {
     pub fn new(id: K) -> Self {
-        Self { id }
+        Self {
+            id,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Reads the timestamps from the given clock instead of the local one
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
     }
 }
 
@@ -22,7 +34,7 @@ where
     F: FrameProperties<K, u128> + Send + 'static,
 {
     async fn process(&mut self, mut frame_data: F) -> Option<F> {
-        frame_data.set(self.id, now_timestamp());
+        frame_data.set(self.id, self.clock.now());
         Some(frame_data)
     }
 }
@@ -1,13 +1,16 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 
 use remotia_core::{
-    common::helpers::time::now_timestamp,
+    common::helpers::time::{Clock, SystemClock},
     traits::{FrameProcessor, FrameProperties},
 };
 
 pub struct TimestampDiffCalculator<K> {
     source_id: K,
     diff_id: K,
+    clock: Arc<dyn Clock>,
 }
 
 impl<K> TimestampDiffCalculator<K> {
@@ -15,8 +18,16 @@ impl<K> TimestampDiffCalculator<K> {
         Self {
             source_id,
             diff_id,
+            clock: Arc::new(SystemClock),
         }
     }
+
+    /// Reads the current time from the given clock instead of the local one, e.g. a clock
+    /// synchronized with the host which produced the source timestamp
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
 }
 
 #[async_trait]
@@ -27,7 +38,10 @@ where
 {
     async fn process(&mut self, mut frame_data: F) -> Option<F> {
         let source_timestamp = frame_data.get(&self.source_id).unwrap();
-        frame_data.set(self.diff_id, now_timestamp() - source_timestamp);
+        frame_data.set(
+            self.diff_id,
+            self.clock.now().saturating_sub(source_timestamp),
+        );
         Some(frame_data)
     }
 }
@@ -30,3 +30,13 @@ pub mod profilation {
 pub mod serialization {
     pub use remotia_serialization_utils::*;
 }
+
+#[cfg(feature = "image")]
+pub mod image {
+    pub use remotia_image_utils::*;
+}
+
+#[cfg(feature = "input")]
+pub mod input {
+    pub use remotia_input_utils::*;
+}